@@ -0,0 +1,198 @@
+//! Instruction set covering both the day 18 and the day 23 assembly dialects, so programs from
+//! either day can run on the same machine.
+
+use std::fmt::Display;
+use std::ops::{Index, IndexMut};
+
+/// Register, by its letter `a` to `z`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Reg(u8);
+
+impl Reg {
+    pub const fn new(letter: u8) -> Option<Self> {
+        if letter.is_ascii_lowercase() {
+            Some(Self(letter - b'a'))
+        } else {
+            None
+        }
+    }
+
+    pub const fn letter(self) -> u8 {
+        self.0 + b'a'
+    }
+}
+
+impl Display for Reg {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.letter() as char)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RegOrValue {
+    Reg(Reg),
+    Value(i64),
+}
+
+impl Display for RegOrValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Reg(reg) => reg.fmt(f),
+            Self::Value(v) => v.fmt(f),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BinOp {
+    Set,
+    Add,
+    Sub,
+    Mul,
+    Mod,
+}
+
+impl BinOp {
+    const fn apply(self, target: &mut i64, rhs: i64) {
+        match self {
+            Self::Set => *target = rhs,
+            Self::Add => *target = target.checked_add(rhs).expect("overflow"),
+            Self::Sub => *target = target.checked_sub(rhs).expect("overflow"),
+            Self::Mul => *target = target.checked_mul(rhs).expect("overflow"),
+            Self::Mod => *target = target.checked_rem(rhs).expect("overflow"),
+        }
+    }
+}
+
+impl Display for BinOp {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Set => f.write_str("set"),
+            Self::Add => f.write_str("add"),
+            Self::Sub => f.write_str("sub"),
+            Self::Mul => f.write_str("mul"),
+            Self::Mod => f.write_str("mod"),
+        }
+    }
+}
+
+/// When a jump is taken.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Condition {
+    /// `jgz`, from day 18
+    GreaterThanZero,
+    /// `jnz`, from day 23
+    NonZero,
+}
+
+impl Condition {
+    const fn holds(self, value: i64) -> bool {
+        match self {
+            Self::GreaterThanZero => value > 0,
+            Self::NonZero => value != 0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Instruction {
+    Snd(RegOrValue),
+    Rcv(Reg),
+    BinOp(BinOp, Reg, RegOrValue),
+    Jump(Condition, RegOrValue, RegOrValue),
+}
+
+impl Display for Instruction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Snd(value) => write!(f, "snd {value}"),
+            Self::Rcv(reg) => write!(f, "rcv {reg}"),
+            Self::BinOp(op, reg, value) => write!(f, "{op} {reg} {value}"),
+            Self::Jump(Condition::GreaterThanZero, check, delta) => {
+                write!(f, "jgz {check} {delta}")
+            }
+            Self::Jump(Condition::NonZero, check, delta) => write!(f, "jnz {check} {delta}"),
+        }
+    }
+}
+
+/// Runs a program in the sound-and-recover interpretation of day 18, which for day 23 programs
+/// (that have neither `snd` nor `rcv`) is simply running until the end.
+#[derive(Debug, Clone)]
+pub struct Machine<'a> {
+    instructions: &'a [Instruction],
+    ip: usize,
+    registers: [i64; 26],
+    last_sound: Option<i64>,
+    mul_count: usize,
+}
+
+#[cfg_attr(not(test), expect(dead_code, reason = "No puzzle runs on the shared machine yet."))]
+impl<'a> Machine<'a> {
+    pub const fn new(instructions: &'a [Instruction]) -> Self {
+        Self {
+            instructions,
+            ip: 0,
+            registers: [0; 26],
+            last_sound: None,
+            mul_count: 0,
+        }
+    }
+
+    fn get_value(&self, source: RegOrValue) -> i64 {
+        match source {
+            RegOrValue::Reg(reg) => self[reg],
+            RegOrValue::Value(val) => val,
+        }
+    }
+
+    /// Runs until the first `rcv` with a nonzero argument, and returns the last played sound.
+    /// Returns `None` if the program jumps or runs out of the instructions first.
+    pub fn run_until_recover(&mut self) -> Option<i64> {
+        while let Some(&instr) = self.instructions.get(self.ip) {
+            match instr {
+                Instruction::Snd(value) => self.last_sound = Some(self.get_value(value)),
+                Instruction::Rcv(reg) => {
+                    if self[reg] != 0 {
+                        return self.last_sound;
+                    }
+                }
+                Instruction::BinOp(op, reg, rhs) => {
+                    let rhs = self.get_value(rhs);
+                    op.apply(&mut self[reg], rhs);
+                    if op == BinOp::Mul {
+                        self.mul_count += 1;
+                    }
+                }
+                Instruction::Jump(condition, check, delta) => {
+                    if condition.holds(self.get_value(check)) {
+                        let delta = isize::try_from(self.get_value(delta)).unwrap();
+                        self.ip = self.ip.checked_add_signed(delta)?;
+                        continue;
+                    }
+                }
+            }
+            self.ip += 1;
+        }
+        None
+    }
+
+    /// Number of `mul` instructions executed.
+    pub const fn mul_count(&self) -> usize {
+        self.mul_count
+    }
+}
+
+impl Index<Reg> for Machine<'_> {
+    type Output = i64;
+
+    fn index(&self, reg: Reg) -> &Self::Output {
+        &self.registers[reg.0 as usize]
+    }
+}
+
+impl IndexMut<Reg> for Machine<'_> {
+    fn index_mut(&mut self, reg: Reg) -> &mut Self::Output {
+        &mut self.registers[reg.0 as usize]
+    }
+}
@@ -9,6 +9,32 @@ fn part_1(input: &[u8]) -> u64 {
     sum
 }
 
+#[aoc(day1, part1, Fast)]
+fn part_1_fast(input: &[u8]) -> u64 {
+    const LANES: usize = 32;
+    let Some(&last) = input.last() else {
+        return 0;
+    };
+    // The last digit is compared with the first
+    let mut sum = u64::from(last == input[0]) * u64::from(last.wrapping_sub(b'0'));
+    let lhs = &input[..input.len() - 1];
+    let rhs = &input[1..];
+    let mut lhs_chunks = lhs.chunks_exact(LANES);
+    let mut rhs_chunks = rhs.chunks_exact(LANES);
+    for (l, r) in (&mut lhs_chunks).zip(&mut rhs_chunks) {
+        // Multiply by an equality mask instead of branching, so the loop can be vectorized
+        let mut chunk_sum = 0_u32;
+        for (&a, &b) in l.iter().zip(r) {
+            chunk_sum += u32::from(a == b) * u32::from(a.wrapping_sub(b'0'));
+        }
+        sum += u64::from(chunk_sum);
+    }
+    for (&a, &b) in lhs_chunks.remainder().iter().zip(rhs_chunks.remainder()) {
+        sum += u64::from(a == b) * u64::from(a.wrapping_sub(b'0'));
+    }
+    sum
+}
+
 #[aoc(day1, part2)]
 fn part_2(input: &[u8]) -> u64 {
     let mut sum = 0;
@@ -20,6 +46,21 @@ fn part_2(input: &[u8]) -> u64 {
     sum
 }
 
+/// Sum of the digits that match the digit `offset` steps ahead, wrapping around. Each byte is
+/// read as a digit in `radix`, like [`char::to_digit`].
+#[allow(unused)]
+fn matching_sum_radix(input: &[u8], offset: usize, radix: u32) -> u64 {
+    let mut sum = 0;
+    for (&a, &b) in input.iter().zip(input.iter().cycle().skip(offset)) {
+        if a == b {
+            sum += u64::from(char::from(a).to_digit(radix).expect("Invalid digit"));
+        }
+    }
+    sum
+}
+
+solver!(raw; 1 => part_1, 2 => part_2);
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -33,6 +74,21 @@ mod tests {
         part_1(input)
     }
 
+    #[test_case(b"1122", 1)]
+    #[test_case(b"1111", 1)]
+    #[test_case(b"1234", 1)]
+    #[test_case(b"91212129", 1)]
+    #[test_case(b"7", 1)]
+    #[test_case(b"9", 32)]
+    #[test_case(b"9", 33)]
+    #[test_case(b"1122", 8)]
+    #[test_case(b"1122", 9)]
+    #[test_case(b"9009193", 100)]
+    fn test_part_1_fast(pattern: &[u8], repeat: usize) {
+        let input = pattern.repeat(repeat);
+        assert_eq!(part_1_fast(&input), part_1(&input));
+    }
+
     #[test_case(b"1212" => 6)]
     #[test_case(b"1221" => 0)]
     #[test_case(b"123425" => 4)]
@@ -41,4 +97,20 @@ mod tests {
     fn test_part_2(input: &[u8]) -> u64 {
         part_2(input)
     }
-}
\ No newline at end of file
+
+    #[test_case(b"aa", 1 => 20)]
+    #[test_case(b"1f1f", 2 => 32)]
+    #[test_case(b"ABab", 1 => 0)]
+    #[test_case(b"FfFf", 2 => 60)]
+    fn test_matching_sum_hex(input: &[u8], offset: usize) -> u64 {
+        matching_sum_radix(input, offset, 16)
+    }
+
+    #[test_case(b"91212129")]
+    #[test_case(b"123123")]
+    #[test_case(b"12131415")]
+    fn test_matching_sum_decimal(input: &[u8]) {
+        assert_eq!(matching_sum_radix(input, 1, 10), part_1(input));
+        assert_eq!(matching_sum_radix(input, input.len() / 2, 10), part_2(input));
+    }
+}
@@ -21,6 +21,18 @@ fn part_1(input: &[Vec<i64>]) -> i64 {
         .sum()
 }
 
+/// Like [`part_1`], but accumulates in `i128`, so it can't overflow. Empty rows count as zero.
+#[allow(unused)]
+fn part_1_wide(input: &[Vec<i64>]) -> i128 {
+    input
+        .iter()
+        .map(|row| match (row.iter().min(), row.iter().max()) {
+            (Some(&min), Some(&max)) => i128::from(max) - i128::from(min),
+            _ => 0,
+        })
+        .sum()
+}
+
 #[aoc(day2, part2)]
 fn part_2(input: &[Vec<i64>]) -> i64 {
     input
@@ -40,6 +52,8 @@ fn part_2(input: &[Vec<i64>]) -> i64 {
         .sum()
 }
 
+solver!(parse; 1 => part_1, 2 => part_2);
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -75,4 +89,14 @@ mod tests {
         let result = part_2(&input);
         assert_eq!(result, 9);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_part_1_wide() {
+        let input = parse(EXAMPLE1).unwrap();
+        assert_eq!(part_1_wide(&input), i128::from(part_1(&input)));
+        let input = vec![vec![i64::MIN, i64::MAX], vec![0, i64::MAX], vec![-1, 1]];
+        let expected = 2 * i128::from(i64::MAX) + 1 + i128::from(i64::MAX) + 2;
+        assert_eq!(part_1_wide(&input), expected);
+        assert!(part_1_wide(&input) > i128::from(i64::MAX));
+    }
+}
@@ -12,8 +12,23 @@ fn part_1(&input: &u64) -> u64 {
     x.unsigned_abs() + y.unsigned_abs()
 }
 
+/// Which square ring around the center `value` is on, with the center itself being ring 0.
+const fn ring_of(value: u64) -> u64 {
+    (value - 1).isqrt().div_ceil(2)
+}
+
+/// The first and last value on `ring`.
+#[allow(unused)]
+const fn ring_bounds(ring: u64) -> (u64, u64) {
+    if ring == 0 {
+        (1, 1)
+    } else {
+        ((2 * ring - 1) * (2 * ring - 1) + 1, (2 * ring + 1) * (2 * ring + 1))
+    }
+}
+
 fn get_position(value: u64) -> (i64, i64) {
-    let ring = i64::try_from((value - 1).isqrt().div_ceil(2)).unwrap();
+    let ring = i64::try_from(ring_of(value)).unwrap();
     let value = i64::try_from(value).unwrap();
     // +x is right, +y is up
     if value <= (4 * ring - 2) * ring + 1 {
@@ -40,9 +55,15 @@ fn get_position(value: u64) -> (i64, i64) {
 #[aoc(day3, part2)]
 #[expect(clippy::trivially_copy_pass_by_ref, reason = "AOC library")]
 fn part_2(&input: &u64) -> u64 {
+    stress_test_values().find(|&v| v > input).unwrap()
+}
+
+/// Values written by the stress test, where each square is the sum of its already filled
+/// neighbors. (OEIS A141481)
+fn stress_test_values() -> impl Iterator<Item = u64> {
     let mut values = HashMap::new();
     values.insert((0, 0), 1);
-    for n in 2.. {
+    std::iter::once(1).chain((2..).map(move |n| {
         let (x, y) = get_position(n);
         let mut sum = 0;
         for x1 in x - 1..=x + 1 {
@@ -52,14 +73,13 @@ fn part_2(&input: &u64) -> u64 {
                 }
             }
         }
-        if sum > input {
-            return sum;
-        }
         values.insert((x, y), sum);
-    }
-    unreachable!("Overflow?")
+        sum
+    }))
 }
 
+solver!(parse; 1 => part_1, 2 => part_2);
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -80,6 +100,26 @@ mod tests {
     }
 
 
+    #[test_case(1 => 0)]
+    #[test_case(2 => 1)]
+    #[test_case(9 => 1)]
+    #[test_case(10 => 2)]
+    #[test_case(25 => 2)]
+    #[test_case(26 => 3)]
+    fn test_ring_of(value: u64) -> u64 {
+        ring_of(value)
+    }
+
+    #[test_case(0 => (1, 1))]
+    #[test_case(1 => (2, 9))]
+    #[test_case(2 => (10, 25))]
+    #[test_case(3 => (26, 49))]
+    fn test_ring_bounds(ring: u64) -> (u64, u64) {
+        let (first, last) = ring_bounds(ring);
+        assert!((first..=last).all(|v| ring_of(v) == ring));
+        (first, last)
+    }
+
     #[test_case(1 => 0)]
     #[test_case(12 => 3)]
     #[test_case(23 => 2)]
@@ -87,4 +127,17 @@ mod tests {
     fn test_part_1(input: u64) -> u64 {
         part_1(&input)
     }
+
+    #[test]
+    fn test_stress_test_values() {
+        let values = stress_test_values().take(12).collect::<Vec<_>>();
+        assert_eq!(values, [1, 1, 2, 4, 5, 10, 11, 23, 25, 26, 54, 57]);
+    }
+
+    #[test_case(1 => 2)]
+    #[test_case(10 => 11)]
+    #[test_case(747 => 806)]
+    fn test_part_2(input: u64) -> u64 {
+        part_2(&input)
+    }
 }
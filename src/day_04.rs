@@ -14,6 +14,15 @@ fn unique_words<'a>(passphrase: &'a str, seen: &mut HashSet<&'a str>) -> bool {
     passphrase.split(' ').all(|word| seen.insert(word))
 }
 
+/// Same as [`unique_words`], but sorts the words instead of hashing them.
+#[allow(unused)]
+fn unique_words_sorted<'a>(passphrase: &'a str, words: &mut Vec<&'a str>) -> bool {
+    words.clear();
+    words.extend(passphrase.split(' '));
+    words.sort_unstable();
+    words.windows(2).all(|pair| pair[0] != pair[1])
+}
+
 #[aoc(day4, part2)]
 fn part_2(input: &str) -> usize {
     let mut seen = HashSet::new();
@@ -34,6 +43,8 @@ fn unique_palindromes(passphrase: &str, seen: &mut HashSet<[u8; 26]>) -> bool {
     })
 }
 
+solver!(raw; 1 => part_1, 2 => part_2);
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -46,6 +57,28 @@ mod tests {
         unique_words(line, &mut HashSet::new())
     }
 
+    #[test]
+    fn test_unique_words_sorted() {
+        let lines = [
+            "aa bb cc dd ee",
+            "aa bb cc dd aa",
+            "aa bb cc dd aaa",
+            "a",
+            "zz yy xx zz",
+            "b a c a",
+            "one two three four five six seven eight nine ten",
+        ];
+        let mut seen = HashSet::new();
+        let mut words = Vec::new();
+        for line in lines {
+            assert_eq!(
+                unique_words_sorted(line, &mut words),
+                unique_words(line, &mut seen),
+                "{line}"
+            );
+        }
+    }
+
     #[test_case("abcde fghij" => true)]
     #[test_case("abcde xyz ecdab" => false)]
     #[test_case("a ab abc abd abf abj" => true)]
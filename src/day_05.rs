@@ -18,14 +18,57 @@ fn part_2(input: &[isize]) -> usize {
 }
 
 fn run(offsets: &mut [isize], part2: bool) -> usize {
+    run_with_max_ip(offsets, part2).0
+}
+
+/// Returns the number of steps, and the highest instruction pointer that was executed.
+fn run_with_max_ip(offsets: &mut [isize], part2: bool) -> (usize, usize) {
+    run_custom_with_max_ip(offsets, 1, part2.then_some((3, -1)))
+}
+
+/// Like [`run`], but each offset is changed by `inc` after the jump, or by `delta` if
+/// `dec_above` is `Some((threshold, delta))` and the offset was at least `threshold`.
+#[allow(unused)]
+fn run_custom(offsets: &mut [isize], inc: isize, dec_above: Option<(isize, isize)>) -> usize {
+    run_custom_with_max_ip(offsets, inc, dec_above).0
+}
+
+fn run_custom_with_max_ip(
+    offsets: &mut [isize],
+    inc: isize,
+    dec_above: Option<(isize, isize)>,
+) -> (usize, usize) {
+    let mut max_ip = 0;
+    let steps = run_custom_visiting(offsets, inc, dec_above, |ip| max_ip = max_ip.max(ip));
+    (steps, max_ip)
+}
+
+/// The instruction pointer of every executed jump, in order.
+#[allow(unused)]
+fn run_trace(offsets: &mut [isize], part2: bool) -> Vec<usize> {
+    let mut trace = Vec::new();
+    run_custom_visiting(offsets, 1, part2.then_some((3, -1)), |ip| trace.push(ip));
+    trace
+}
+
+/// Runs the jumps as in [`run_custom`], calling `visit(ip)` before each one.
+fn run_custom_visiting<F>(
+    offsets: &mut [isize],
+    inc: isize,
+    dec_above: Option<(isize, isize)>,
+    mut visit: F,
+) -> usize
+where
+    F: FnMut(usize),
+{
     let mut ip = 0_usize;
     let mut steps = 0;
     while let Some(&jump) = offsets.get(ip) {
         steps += 1;
-        offsets[ip] = if part2 && jump >= 3 {
-            jump - 1
-        } else {
-            jump + 1
+        visit(ip);
+        offsets[ip] = match dec_above {
+            Some((threshold, delta)) if jump >= threshold => jump + delta,
+            _ => jump + inc,
         };
         let Some(new_ip) = ip.checked_add_signed(jump)else {
             break;
@@ -35,6 +78,8 @@ fn run(offsets: &mut [isize], part2: bool) -> usize {
     steps
 }
 
+solver!(parse; 1 => part_1, 2 => part_2);
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -47,6 +92,14 @@ mod tests {
         assert_eq!(offsets, [2, 5, 0, 1, -2]);
     }
 
+    #[test]
+    fn test_run_with_max_ip() {
+        let mut offsets = [0, 3, 0, 1, -3];
+        let (steps, max_ip) = run_with_max_ip(&mut offsets, false);
+        assert_eq!(steps, 5);
+        assert_eq!(max_ip, 4);
+    }
+
     #[test]
     fn test_part_2() {
         let mut offsets = [0, 3, 0, 1, -3];
@@ -54,4 +107,30 @@ mod tests {
         assert_eq!(result, 10);
         assert_eq!(offsets, [2, 3, 2, 3, -1]);
     }
+
+    #[test]
+    fn test_run_custom() {
+        let mut offsets = [0, 3, 0, 1, -3];
+        assert_eq!(run_custom(&mut offsets, 1, Some((3, -1))), 10);
+        assert_eq!(offsets, [2, 3, 2, 3, -1]);
+        let mut offsets = [0, 3, 0, 1, -3];
+        assert_eq!(run_custom(&mut offsets, 1, None), 5);
+        assert_eq!(offsets, [2, 5, 0, 1, -2]);
+        let mut offsets = [0, 3, 0, 1, -3];
+        assert_eq!(run_custom(&mut offsets, 2, None), 9);
+        assert_eq!(offsets, [4, 5, 4, 3, 3]);
+    }
+
+    #[test]
+    fn test_run_trace() {
+        let mut offsets = [0, 3, 0, 1, -3];
+        let trace = run_trace(&mut offsets, false);
+        assert_eq!(trace, [0, 0, 1, 4, 1]);
+        assert_eq!(offsets, [2, 5, 0, 1, -2]);
+
+        let mut offsets = [0, 3, 0, 1, -3];
+        let trace = run_trace(&mut offsets, true);
+        assert_eq!(trace.len(), run(&mut [0, 3, 0, 1, -3], true));
+        assert_eq!(trace[..5], [0, 0, 1, 4, 1]);
+    }
 }
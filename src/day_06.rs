@@ -1,8 +1,44 @@
 use std::num::ParseIntError;
 
+use thiserror::Error;
+
+use crate::utils::{CycleState, brent};
+
+#[derive(Debug, Error)]
+enum ParseError {
+    #[error("No memory banks given")]
+    EndOfInput,
+    #[error(transparent)]
+    InvalidNumber(#[from] ParseIntError),
+    #[error("Bank value {0} does not fit in a byte")]
+    ValueOutOfRange(u64),
+    #[error("Total of {0} blocks could overflow a single bank")]
+    TotalOutOfRange(u64),
+    #[error("Expected 4 or 16 memory banks, found {0}")]
+    UnsupportedBankCount(usize),
+}
+
 #[aoc_generator(day6)]
-fn parse(input: &str) -> Result<Vec<u8>, ParseIntError> {
-    input.split_ascii_whitespace().map(str::parse).collect()
+fn parse(input: &str) -> Result<Vec<u8>, ParseError> {
+    let banks = input
+        .split_ascii_whitespace()
+        .map(|word| {
+            let value = word.parse::<u64>()?;
+            u8::try_from(value).map_err(|_| ParseError::ValueOutOfRange(value))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    if banks.is_empty() {
+        return Err(ParseError::EndOfInput);
+    }
+    // All blocks can end up in the same bank
+    let total = banks.iter().map(|&b| u64::from(b)).sum::<u64>();
+    if total > u64::from(u8::MAX) {
+        return Err(ParseError::TotalOutOfRange(total));
+    }
+    if !matches!(banks.len(), 4 | 16) {
+        return Err(ParseError::UnsupportedBankCount(banks.len()));
+    }
+    Ok(banks)
 }
 
 #[aoc(day6, part1)]
@@ -25,37 +61,13 @@ fn part_2(input: &[u8]) -> usize {
 
 /// Brent's algorithm
 fn find_loop<const N: usize>(start: [u8; N]) -> (usize, usize) {
-    let mut slow = start;
-    let mut fast = start;
-    step(&mut fast);
-
-    let mut power = 1;
-    let mut length = 1;
-
-    while slow != fast {
-        if length == power {
-            slow = fast;
-            power *= 2;
-            length = 0;
-        }
-        step(&mut fast);
-        length += 1;
-    }
-
-    slow = start;
-    fast = start;
-    for _ in 0..length {
-        step(&mut fast);
-    }
+    brent(start)
+}
 
-    let mut offset = 0;
-    while slow != fast {
-        step(&mut fast);
-        step(&mut slow);
-        offset += 1;
+impl<const N: usize> CycleState for [u8; N] {
+    fn step(&mut self) {
+        step(self);
     }
-
-    (length, offset)
 }
 
 fn step<const N: usize>(state: &mut [u8; N]) {
@@ -74,6 +86,8 @@ fn step<const N: usize>(state: &mut [u8; N]) {
     }
 }
 
+solver!(parse; 1 => part_1, 2 => part_2);
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -96,4 +110,20 @@ mod tests {
         assert_eq!(len + offset, 5);
         assert_eq!(len, 4);
     }
+
+    #[test]
+    fn test_brent() {
+        assert_eq!(brent([0_u8, 2, 7, 0]), (4, 1));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(parse("0 2\t7 0").unwrap(), [0, 2, 7, 0]);
+        assert!(matches!(parse(""), Err(ParseError::EndOfInput)));
+        assert!(matches!(parse(" \n"), Err(ParseError::EndOfInput)));
+        assert!(matches!(parse("999"), Err(ParseError::ValueOutOfRange(999))));
+        assert!(matches!(parse("200 100"), Err(ParseError::TotalOutOfRange(300))));
+        assert!(matches!(parse("1 x"), Err(ParseError::InvalidNumber(_))));
+        assert!(matches!(parse("1 2 3"), Err(ParseError::UnsupportedBankCount(3))));
+    }
 }
@@ -1,3 +1,4 @@
+use std::cell::OnceCell;
 use std::collections::{HashMap, VecDeque};
 use std::num::ParseIntError;
 use std::str::FromStr;
@@ -48,6 +49,67 @@ impl Program {
 struct Towers {
     string_pool: Vec<String>,
     programs: Vec<Program>,
+    total_weight: OnceCell<Vec<u64>>,
+}
+
+impl Towers {
+    /// Program indices ordered so that every program comes after all of its children
+    pub fn topological_order(&self) -> Vec<usize> {
+        let n = self.programs.len();
+        let mut done = vec![false; n];
+        let mut order = Vec::with_capacity(n);
+        let mut waiting_on = vec![vec![]; n];
+        let mut queue: VecDeque<_> = (0..n).collect();
+
+        'next_in_queue: while let Some(index) = queue.pop_front() {
+            for &child in &self.programs[index].children {
+                if !done[child] {
+                    waiting_on[child].push(index);
+                    continue 'next_in_queue;
+                }
+            }
+            done[index] = true;
+            order.push(index);
+            queue.extend(waiting_on[index].drain(..));
+        }
+        order
+    }
+
+    /// Cumulative weight of each program and everything it carries
+    pub fn total_weights(&self) -> Vec<u64> {
+        self.total_weight
+            .get_or_init(|| calculate_total_weight(self))
+            .clone()
+    }
+
+    /// Programs whose children do not all carry the same total weight
+    #[allow(unused)]
+    pub fn unbalanced_nodes(&self) -> Vec<usize> {
+        let total_weight = self.total_weights();
+        self.programs
+            .iter()
+            .filter(|program| {
+                program
+                    .children
+                    .windows(2)
+                    .any(|pair| total_weight[pair[0]] != total_weight[pair[1]])
+            })
+            .map(|program| program.name)
+            .collect()
+    }
+
+    /// Changes the weight of a program, and forgets the cached total weights
+    #[allow(unused)]
+    pub fn apply_fix(&mut self, node: usize, new_weight: u64) {
+        self.programs[node].weight = new_weight;
+        self.total_weight.take();
+    }
+
+    /// Whether every program has children that carry the same total weight
+    #[allow(unused)]
+    pub fn is_balanced(&self) -> bool {
+        self.unbalanced_nodes().is_empty()
+    }
 }
 
 impl FromStr for Towers {
@@ -70,6 +132,7 @@ impl FromStr for Towers {
         Ok(Self {
             string_pool,
             programs,
+            total_weight: OnceCell::new(),
         })
     }
 }
@@ -99,7 +162,7 @@ fn part_1(towers: &Towers) -> String {
 #[aoc(day7, part2)]
 fn part_2(towers: &Towers) -> u64 {
     // Find the unique program that causes unbalance
-    let total_weight = calculate_total_weight(towers);
+    let total_weight = towers.total_weights();
     for program in &towers.programs {
         if program.children.is_empty() {
             continue;
@@ -118,23 +181,15 @@ fn part_2(towers: &Towers) -> u64 {
 }
 
 fn calculate_total_weight(towers: &Towers) -> Vec<u64> {
-    let n = towers.programs.len();
-    let mut total_weight = vec![0; n];
-    let mut waiting_on = vec![vec![]; n];
-    let mut queue: VecDeque<_> = (0..n).collect();
-
-    'next_in_queue: while let Some(index) = queue.pop_front() {
-        let mut sum = towers.programs[index].weight;
-        for &child in &towers.programs[index].children {
-            let child_weight = total_weight[child];
-            if child_weight == 0 {
-                waiting_on[child].push(index);
-                continue 'next_in_queue;
-            }
-            sum += child_weight;
-        }
-        total_weight[index] = sum;
-        queue.extend(waiting_on[index].drain(..));
+    let mut total_weight = vec![0; towers.programs.len()];
+    for index in towers.topological_order() {
+        let program = &towers.programs[index];
+        total_weight[index] = program.weight
+            + program
+                .children
+                .iter()
+                .map(|&child| total_weight[child])
+                .sum::<u64>();
     }
     total_weight
 }
@@ -176,6 +231,8 @@ fn find_unbalanced(children: &[usize], total_weight: &[u64]) -> Option<(u64, u64
     Some((common_weight?, unique_weight?, unique_index?))
 }
 
+solver!(parse; 1 => part_1, 2 => part_2);
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -250,10 +307,58 @@ mod tests {
         assert_eq!(root_name, "tknk");
     }
 
+    #[test]
+    fn test_total_weights() {
+        let towers = parse(EXAMPLE).unwrap();
+        let root = towers.string_pool.iter().position(|n| n == "tknk").unwrap();
+        let total_weights = towers.total_weights();
+        assert_eq!(total_weights[root], 778);
+    }
+
+    #[test]
+    fn test_unbalanced_nodes() {
+        let towers = parse(EXAMPLE).unwrap();
+        let unbalanced = towers.unbalanced_nodes();
+        assert_eq!(unbalanced.len(), 1);
+        assert_eq!(towers.string_pool[unbalanced[0]], "tknk");
+    }
+
+    #[test]
+    fn test_topological_order() {
+        let towers = parse(EXAMPLE).unwrap();
+        let order = towers.topological_order();
+        assert_eq!(order.len(), towers.programs.len());
+        let mut position = vec![0; order.len()];
+        for (pos, &index) in order.iter().enumerate() {
+            position[index] = pos;
+        }
+        for program in &towers.programs {
+            for &child in &program.children {
+                assert!(position[child] < position[program.name]);
+            }
+        }
+        assert_eq!(towers.string_pool[*order.last().unwrap()], "tknk");
+    }
+
     #[test]
     fn test_part_2() {
         let towers = parse(EXAMPLE).unwrap();
         let updated_weight = part_2(&towers);
         assert_eq!(updated_weight, 60);
     }
+
+    #[test]
+    fn test_apply_fix() {
+        let mut towers = parse(EXAMPLE).unwrap();
+        assert!(!towers.is_balanced());
+        let ugml = towers.string_pool.iter().position(|n| n == "ugml").unwrap();
+        let root = towers.string_pool.iter().position(|n| n == "tknk").unwrap();
+        assert_eq!(towers.total_weights()[ugml], 251);
+        let new_weight = part_2(&towers);
+        assert_eq!(new_weight, 60);
+        towers.apply_fix(ugml, new_weight);
+        assert!(towers.is_balanced());
+        assert_eq!(towers.total_weights()[ugml], 243);
+        assert_eq!(towers.total_weights()[root], 41 + 3 * 243);
+    }
 }
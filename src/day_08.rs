@@ -25,11 +25,13 @@ enum Operation {
 }
 
 impl Operation {
+    /// Applies the operation, clamping the register to the range of `i64` instead of wrapping.
+    /// Overflow always saturates; there is no checked mode, since real inputs never get close.
     const fn apply(self, curr: &mut i64, delta: i64) -> i64 {
-        match self {
-            Self::Inc => *curr += delta,
-            Self::Dec => *curr -= delta,
-        }
+        *curr = match self {
+            Self::Inc => curr.saturating_add(delta),
+            Self::Dec => curr.saturating_sub(delta),
+        };
         *curr
     }
 }
@@ -181,6 +183,13 @@ impl<'a> Machine<'a> {
         }
     }
 
+    /// Zeroes the registers and `max_value`, keeping the allocation.
+    #[allow(unused)]
+    fn reset(&mut self) {
+        self.registers.fill(0);
+        self.max_value = 0;
+    }
+
     fn run(&mut self) {
         for &instr in &self.program.instructions {
             let Instruction {
@@ -198,6 +207,21 @@ impl<'a> Machine<'a> {
             }
         }
     }
+
+    /// Runs the program repeatedly, until a pass leaves the registers unchanged, or after
+    /// `max_passes` passes. Returns the number of passes, including the last one.
+    #[allow(unused)]
+    fn run_to_fixpoint(&mut self, max_passes: usize) -> usize {
+        let mut previous = self.registers.clone();
+        for pass in 1..=max_passes {
+            self.run();
+            if self.registers == previous {
+                return pass;
+            }
+            previous.copy_from_slice(&self.registers);
+        }
+        max_passes
+    }
 }
 
 #[aoc(day8, part1)]
@@ -214,6 +238,8 @@ fn part_2(program: &Program) -> i64 {
     machine.max_value
 }
 
+solver!(parse; 1 => part_1, 2 => part_2);
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -321,4 +347,48 @@ mod tests {
         assert_eq!(machine.max_value, 10);
     }
 
+    #[test]
+    fn test_reset() {
+        let program = parse(EXAMPLE).unwrap();
+        let mut machine = Machine::new(&program);
+        machine.run();
+        let registers = machine.registers.as_ptr();
+        for _ in 0..2 {
+            machine.reset();
+            assert_eq!(machine.registers, [0, 0, 0]);
+            assert_eq!(machine.max_value, 0);
+            machine.run();
+            assert_eq!(machine.registers, [0, 1, -10]);
+            assert_eq!(machine.max_value, 10);
+        }
+        assert_eq!(machine.registers.as_ptr(), registers);
+    }
+
+    #[test]
+    fn test_run_to_fixpoint() {
+        let program = parse("a inc 1 if a < 3\nb inc 2 if a == 2").unwrap();
+        let mut machine = Machine::new(&program);
+        assert_eq!(machine.run_to_fixpoint(10), 4);
+        assert_eq!(machine.registers, [3, 2]);
+        let program = parse("a inc 1 if a > -1").unwrap();
+        let mut machine = Machine::new(&program);
+        assert_eq!(machine.run_to_fixpoint(10), 10);
+        assert_eq!(machine.registers, [10]);
+        assert_eq!(machine.max_value, 10);
+    }
+
+    #[test]
+    fn test_apply_overflow() {
+        let mut reg = i64::MAX - 1;
+        assert_eq!(Operation::Inc.apply(&mut reg, 5), i64::MAX);
+        assert_eq!(reg, i64::MAX);
+        let mut reg = i64::MIN + 1;
+        assert_eq!(Operation::Dec.apply(&mut reg, 5), i64::MIN);
+        assert_eq!(Operation::Inc.apply(&mut reg, 5), i64::MIN + 5);
+
+        let program = parse(&format!("a inc {} if b == 0\na inc 1 if b == 0", i64::MAX)).unwrap();
+        let mut machine = Machine::new(&program);
+        machine.run();
+        assert_eq!(machine.max_value, i64::MAX);
+    }
 }
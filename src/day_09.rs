@@ -16,15 +16,52 @@ fn part_2(input: &[u8]) -> usize {
 }
 
 fn measure(input: &[u8]) -> (usize, usize) {
-    let mut depth = 0;
+    let mut prev_depth = 0;
     let mut score = 0;
     let mut count = 0;
+    measure_with(input, |depth, in_garbage| {
+        if depth > prev_depth {
+            score += depth;
+        }
+        prev_depth = depth;
+        if in_garbage {
+            count += 1;
+        }
+    });
+    (score, count)
+}
+
+/// Length of the longest stretch of counted garbage characters. Cancelled characters break a
+/// stretch, just like the end of the garbage does.
+#[allow(unused)]
+fn longest_garbage_run(input: &[u8]) -> usize {
+    let mut run = 0;
+    let mut longest = 0;
+    measure_with(input, |_, in_garbage| {
+        if in_garbage {
+            run += 1;
+            longest = longest.max(run);
+        } else {
+            run = 0;
+        }
+    });
+    longest
+}
+
+/// Calls `on_char(depth, in_garbage)` for each character, with the group depth after the
+/// character. `in_garbage` is only set for the characters inside garbage that are neither
+/// cancelled nor delimiters, which are the ones counted by [`part_2`].
+fn measure_with<F>(input: &[u8], mut on_char: F)
+where
+    F: FnMut(usize, bool),
+{
+    let mut depth = 0;
     let mut state = State::Normal;
     for ch in input {
+        let mut in_garbage = false;
         state = match (state, ch) {
             (State::Normal, b'{') => {
                 depth += 1;
-                score += depth;
                 State::Normal
             }
             (State::Normal, b'}') => {
@@ -35,14 +72,16 @@ fn measure(input: &[u8]) -> (usize, usize) {
             (State::Normal, _) | (State::String, b'>') => State::Normal,
             (State::String, b'!') => State::Escaped,
             (State::String, _) => {
-                count += 1;
+                in_garbage = true;
                 State::String
             }
         };
+        on_char(depth, in_garbage);
     }
-    (score, count)
 }
 
+solver!(raw; 1 => part_1, 2 => part_2);
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -70,4 +109,40 @@ mod tests {
     fn test_part_2(input: &[u8]) -> usize {
         part_2(input)
     }
+
+    #[test]
+    fn test_measure_with() {
+        let mut histogram = Vec::new();
+        measure_with(b"{{{}}}", |depth, _| {
+            if histogram.len() <= depth {
+                histogram.resize(depth + 1, 0);
+            }
+            histogram[depth] += 1;
+        });
+        assert_eq!(histogram, [1, 2, 2, 1]);
+        let mut garbage = Vec::new();
+        measure_with(b"{<a!>b>}", |depth, in_garbage| garbage.push((depth, in_garbage)));
+        assert_eq!(
+            garbage,
+            [
+                (1, false),
+                (1, false),
+                (1, true),
+                (1, false),
+                (1, false),
+                (1, true),
+                (1, false),
+                (0, false)
+            ]
+        );
+    }
+
+    #[test_case(b"<abcdef>" => 6)]
+    #[test_case(b"<a!b!cd>" => 1)]
+    #[test_case(b"<ab!cdef>" => 3)]
+    #[test_case(b"{<ab>,<cde>}" => 3)]
+    #[test_case(b"{}" => 0)]
+    fn test_longest_garbage_run(input: &[u8]) -> usize {
+        longest_garbage_run(input)
+    }
 }
@@ -1,3 +1,5 @@
+use std::fmt::Write;
+
 use crate::utils::KnotHasher;
 
 #[aoc(day10, part1)]
@@ -14,12 +16,24 @@ fn part_1(input: &str) -> u16 {
 
 #[aoc(day10, part2)]
 fn part_2(input: &str) -> String {
-    let lengths = input.bytes().collect::<Vec<_>>();
-    let mut hasher = KnotHasher::<256>::new(&lengths);
+    let mut hex = String::with_capacity(32);
+    for b in dense_hash_bytes(input) {
+        write!(hex, "{b:02x}").unwrap();
+    }
+    hex
+}
+
+/// The full knot hash of `input`, as raw bytes.
+fn dense_hash_bytes(input: &str) -> [u8; 16] {
+    let mut hasher = KnotHasher::<256>::new(input.as_bytes());
     hasher.scramble_full();
-    hasher.large_hash()
+    let mut hash = [0; 16];
+    hasher.write_hash(&mut hash);
+    hash
 }
 
+solver!(raw; 1 => part_1, 2 => part_2);
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -27,12 +41,36 @@ mod tests {
 
     #[test]
     fn test_part_1() {
-        let mut hasher = KnotHasher::<5>::new(&[3, 4, 1, 5]);
+        let mut hasher = KnotHasher::<5>::with_raw_lengths(&[3, 4, 1, 5]);
         hasher.scramble_once();
         let result = hasher.small_hash();
         assert_eq!(result, 12);
     }
 
+    #[test]
+    fn test_reset_raw() {
+        let mut hasher = KnotHasher::<5>::with_raw_lengths(&[1, 2]);
+        hasher.scramble_once();
+        hasher.reset_raw(&[3, 4, 1, 5]);
+        hasher.scramble_once();
+        let mut fresh = KnotHasher::<5>::with_raw_lengths(&[3, 4, 1, 5]);
+        fresh.scramble_once();
+        assert_eq!(hasher.small_hash(), fresh.small_hash());
+        assert_eq!(hasher.small_hash(), 12);
+    }
+
+    #[test]
+    fn test_into_stream() {
+        let mut hasher = KnotHasher::<256>::new(b"AoC 2017");
+        hasher.scramble_full();
+        let mut hash = [0; 16];
+        hasher.write_hash(&mut hash);
+        let stream = hasher.into_stream().take(48).collect::<Vec<_>>();
+        assert_eq!(stream[..16], hash);
+        assert_ne!(stream[16..32], hash);
+        assert_ne!(stream[32..], stream[16..32]);
+    }
+
     #[test_case("" => "a2582a3a0e66e6e86e3812dcb672a272")]
     #[test_case("AoC 2017" => "33efeb34ea91902bb2f59c9920caa6cd")]
     #[test_case("1,2,3" => "3efbe78a8d82f29979031a4aa0b16a9d")]
@@ -40,4 +78,29 @@ mod tests {
     fn test_part_2(input: &str) -> String {
         part_2(input)
     }
+
+    #[test]
+    #[should_panic(expected = "Length 10 is longer than the list of 5 elements")]
+    fn test_length_too_long() {
+        KnotHasher::<5>::with_raw_lengths(&[3, 10]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Length 6 is longer than the list of 5 elements")]
+    fn test_reset_length_too_long() {
+        let mut hasher = KnotHasher::<5>::with_raw_lengths(&[5]);
+        hasher.reset_raw(&[6]);
+    }
+
+    #[test]
+    fn test_dense_hash_bytes() {
+        let hash = dense_hash_bytes("");
+        assert_eq!(hash[0], 0xa2);
+        assert_eq!(hash[15], 0x72);
+        let hex = hash.iter().fold(String::new(), |mut hex, b| {
+            write!(hex, "{b:02x}").unwrap();
+            hex
+        });
+        assert_eq!(hex, "a2582a3a0e66e6e86e3812dcb672a272");
+    }
 }
@@ -19,6 +19,42 @@ enum Direction {
     NorthWest,
 }
 
+#[allow(unused)]
+impl Direction {
+    const fn all() -> [Self; 6] {
+        [
+            Self::North,
+            Self::NorthEast,
+            Self::SouthEast,
+            Self::South,
+            Self::SouthWest,
+            Self::NorthWest,
+        ]
+    }
+
+    const fn turn_cw(self) -> Self {
+        match self {
+            Self::North => Self::NorthEast,
+            Self::NorthEast => Self::SouthEast,
+            Self::SouthEast => Self::South,
+            Self::South => Self::SouthWest,
+            Self::SouthWest => Self::NorthWest,
+            Self::NorthWest => Self::North,
+        }
+    }
+
+    const fn turn_ccw(self) -> Self {
+        match self {
+            Self::North => Self::NorthWest,
+            Self::NorthEast => Self::North,
+            Self::SouthEast => Self::NorthEast,
+            Self::South => Self::SouthEast,
+            Self::SouthWest => Self::South,
+            Self::NorthWest => Self::SouthWest,
+        }
+    }
+}
+
 impl FromStr for Direction {
     type Err = ParseError;
 
@@ -48,6 +84,50 @@ impl Axial {
             + (self.r + self.q).unsigned_abs())
             / 2
     }
+
+    /// Number of steps from `self` to `other`.
+    const fn distance_to(self, other: Self) -> u64 {
+        Self {
+            q: other.q - self.q,
+            r: other.r - self.r,
+        }
+        .distance()
+    }
+
+    /// Like [`Axial::distance`], but returns `None` instead of overflowing.
+    #[allow(unused)]
+    const fn distance_checked(self) -> Option<u64> {
+        let Some(s) = self.r.checked_add(self.q) else {
+            return None;
+        };
+        let Some(sum) = self.r.unsigned_abs().checked_add(self.q.unsigned_abs()) else {
+            return None;
+        };
+        let Some(sum) = sum.checked_add(s.unsigned_abs()) else {
+            return None;
+        };
+        Some(sum / 2)
+    }
+
+    /// Converts to "odd-q" offset coordinates `(row, col)`.
+    ///
+    /// The hexes are flat-topped (north and south are neighbors), so the columns are the
+    /// straight lines, and odd columns are shoved half a hex down.
+    #[allow(unused)]
+    const fn to_offset(self) -> (i64, i64) {
+        let col = self.q;
+        let row = self.r + (self.q - (self.q & 1)) / 2;
+        (row, col)
+    }
+
+    /// Inverse of [`Axial::to_offset`].
+    #[allow(unused)]
+    const fn from_offset((row, col): (i64, i64)) -> Self {
+        Self {
+            q: col,
+            r: row - (col - (col & 1)) / 2,
+        }
+    }
 }
 
 impl Add for Axial {
@@ -127,6 +207,43 @@ fn part_2(directions: &[Direction]) -> u64 {
         .unwrap()
 }
 
+/// A shortest path with the same endpoint as `path`. Steps in the same direction are grouped
+/// together.
+#[allow(unused)]
+fn simplify(path: &[Direction]) -> Vec<Direction> {
+    let mut remaining = path.iter().copied().fold(Axial::default(), Axial::add);
+    let mut result = Vec::new();
+    while remaining != Axial::default() {
+        let distance = remaining.distance();
+        let (dir, rest) = Direction::all()
+            .into_iter()
+            .map(|dir| {
+                let step = Axial::from(dir);
+                let rest = Axial {
+                    q: remaining.q - step.q,
+                    r: remaining.r - step.r,
+                };
+                (dir, rest)
+            })
+            .find(|&(_, rest)| rest.distance() < distance)
+            .unwrap();
+        result.push(dir);
+        remaining = rest;
+    }
+    result
+}
+
+/// Total number of steps to visit each of the `waypoints` in order.
+#[allow(unused)]
+fn distance_through(waypoints: &[Axial]) -> u64 {
+    waypoints
+        .windows(2)
+        .map(|pair| pair[0].distance_to(pair[1]))
+        .sum()
+}
+
+solver!(parse; 1 => part_1, 2 => part_2);
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -148,4 +265,69 @@ mod tests {
         let directions = parse(input).unwrap();
         part_1(&directions)
     }
+
+    #[test]
+    fn test_distance_checked() {
+        let near = Axial { q: 3, r: -5 };
+        assert_eq!(near.distance_checked(), Some(near.distance()));
+        assert_eq!(Axial { q: i64::MAX, r: 1 }.distance_checked(), None);
+        assert_eq!(Axial { q: i64::MIN, r: -1 }.distance_checked(), None);
+        assert_eq!(Axial { q: i64::MAX, r: i64::MIN }.distance_checked(), None);
+        assert_eq!(Axial { q: i64::MAX, r: 0 }.distance_checked(), Some(i64::MAX as u64));
+    }
+
+    #[test]
+    fn test_turns() {
+        assert_eq!(Direction::North.turn_cw(), Direction::NorthEast);
+        assert_eq!(Direction::North.turn_ccw(), Direction::NorthWest);
+        let mut dir = Direction::North;
+        for _ in 0..6 {
+            assert_eq!(dir.turn_cw().turn_ccw(), dir);
+            dir = dir.turn_cw();
+        }
+        assert_eq!(dir, Direction::North);
+    }
+
+    #[test]
+    fn test_offset_round_trip() {
+        for (q, r) in [(0, 0), (1, 0), (0, 1), (-1, 1), (3, -2), (-3, -5), (7, 4)] {
+            let axial = Axial { q, r };
+            assert_eq!(Axial::from_offset(axial.to_offset()), axial);
+        }
+        // Odd columns are shifted down
+        assert_eq!(Axial::from(Direction::SouthEast).to_offset(), (0, 1));
+        assert_eq!(Axial::from(Direction::NorthEast).to_offset(), (-1, 1));
+    }
+
+    #[test_case("ne,sw" => 0)]
+    #[test_case("ne,ne,s,s" => 2)]
+    #[test_case("se,sw,se,sw,sw" => 3)]
+    #[test_case("n,se,sw,n,nw,ne,s" => 1)]
+    fn test_simplify(input: &str) -> usize {
+        let path = parse(input).unwrap();
+        let simple = simplify(&path);
+        let end = |path: &[Direction]| path.iter().copied().fold(Axial::default(), Axial::add);
+        assert_eq!(end(&simple), end(&path));
+        assert_eq!(u64::try_from(simple.len()).unwrap(), part_1(&path));
+        simple.len()
+    }
+
+    #[test]
+    fn test_simplify_groups() {
+        let path = parse("ne,ne,s,s").unwrap();
+        assert_eq!(simplify(&path), [Direction::SouthEast; 2]);
+    }
+
+    #[test]
+    fn test_distance_through() {
+        let origin = Axial::default();
+        let east = Axial { q: 2, r: 0 };
+        let north_east = Axial { q: 2, r: -3 };
+        assert_eq!(origin.distance_to(east), 2);
+        assert_eq!(east.distance_to(north_east), 3);
+        assert_eq!(north_east.distance_to(origin), north_east.distance());
+        assert_eq!(distance_through(&[origin, east, north_east]), 5);
+        assert_eq!(distance_through(&[origin, east, north_east, origin]), 8);
+        assert_eq!(distance_through(&[east]), 0);
+    }
 }
@@ -1,3 +1,4 @@
+use std::collections::HashSet;
 use std::num::ParseIntError;
 
 use thiserror::Error;
@@ -12,43 +13,87 @@ enum ParseError {
     InvalidNumber(#[from] ParseIntError),
 }
 
+/// The pipes between programs, and the number of programs they connect.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Pipes {
+    num_programs: usize,
+    edges: Vec<(u16, u16)>,
+}
+
+impl Pipes {
+    fn union_find(&self) -> UnionFind {
+        let mut uf = UnionFind::new(self.num_programs);
+        for &(a, b) in &self.edges {
+            uf.union(a as usize, b as usize);
+        }
+        uf
+    }
+}
+
 #[aoc_generator(day12)]
-fn parse(input: &str) -> Result<Vec<(u16, u16)>, ParseError> {
-    let mut result = Vec::new();
+fn parse(input: &str) -> Result<Pipes, ParseError> {
+    parse_edges(input, false)
+}
+
+/// Parses the pipes. With `dedup`, self-loops are skipped, and each pair of programs is only
+/// connected once, regardless of direction. Programs with only a self-loop are still counted.
+fn parse_edges(input: &str, dedup: bool) -> Result<Pipes, ParseError> {
+    let mut num_programs = 0;
+    let mut edges = Vec::new();
+    let mut seen = HashSet::new();
     for line in input.lines() {
         let (left, rights) = line.split_once(" <-> ").ok_or(ParseError::SyntaxError)?;
-        let left = left.parse()?;
+        let left: u16 = left.parse()?;
+        num_programs = num_programs.max(left as usize + 1);
         for right in rights.split(", ") {
-            let right = right.parse()?;
-            result.push((left, right));
+            let right: u16 = right.parse()?;
+            num_programs = num_programs.max(right as usize + 1);
+            if dedup && (left == right || !seen.insert((left.min(right), left.max(right)))) {
+                continue;
+            }
+            edges.push((left, right));
         }
     }
-    Ok(result)
+    Ok(Pipes {
+        num_programs,
+        edges,
+    })
 }
 
 #[aoc(day12, part1)]
-fn part_1(input: &[(u16, u16)]) -> usize {
-    let num_programs = input.iter().map(|&(a, b)| a.max(b)).max().unwrap() as usize + 1;
-    let mut uf = UnionFind::new(num_programs);
-    for &(a, b) in input {
-        uf.union(a as usize, b as usize);
-    }
+fn part_1(input: &Pipes) -> usize {
+    let mut uf = input.union_find();
     let target_group = uf.find(0);
-    (0..num_programs)
+    (0..input.num_programs)
         .filter(|&p| uf.find(p) == target_group)
         .count()
 }
 
 #[aoc(day12, part2)]
-fn part_2(input: &[(u16, u16)]) -> usize {
-    let num_programs = input.iter().map(|&(a, b)| a.max(b)).max().unwrap() as usize + 1;
-    let mut uf = UnionFind::new(num_programs);
-    for &(a, b) in input {
-        uf.union(a as usize, b as usize);
+fn part_2(input: &Pipes) -> usize {
+    input.union_find().num_groups()
+}
+
+/// Every group of connected programs. Each group is sorted, and the groups are ordered by their
+/// smallest member. Without any programs, there are no groups.
+#[allow(unused)]
+fn groups(input: &Pipes) -> Vec<Vec<u16>> {
+    let mut uf = input.union_find();
+    let mut group_index = vec![None; input.num_programs];
+    let mut groups = Vec::<Vec<u16>>::with_capacity(uf.num_groups());
+    for p in 0..input.num_programs {
+        let root = uf.find(p);
+        let index = *group_index[root].get_or_insert_with(|| {
+            groups.push(Vec::new());
+            groups.len() - 1
+        });
+        groups[index].push(u16::try_from(p).unwrap());
     }
-    uf.num_groups()
+    groups
 }
 
+solver!(parse; 1 => part_1, 2 => part_2);
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -68,8 +113,9 @@ mod tests {
     fn test_parse() {
         let result = parse(EXAMPLE).unwrap();
 
+        assert_eq!(result.num_programs, 7);
         assert_eq!(
-            result,
+            result.edges,
             [
                 (0, 2),
                 (1, 1),
@@ -88,6 +134,24 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_parse_dedup() {
+        let result = parse_edges(EXAMPLE, true).unwrap();
+        assert_eq!(result.num_programs, 7);
+        assert_eq!(result.edges, [(0, 2), (2, 3), (2, 4), (3, 4), (4, 6), (5, 6)]);
+        assert_eq!(part_2(&result), 2);
+    }
+
+    #[test]
+    fn test_parse_dedup_trailing_self_loop() {
+        let input = "0 <-> 1\n1 <-> 0\n2 <-> 2";
+        let result = parse_edges(input, true).unwrap();
+        assert_eq!(result.num_programs, 3);
+        assert_eq!(result.edges, [(0, 1)]);
+        assert_eq!(part_2(&result), 2);
+        assert_eq!(groups(&result), [&[0, 1][..], &[2][..]]);
+    }
+
     #[test]
     fn test_part_1() {
         let input = parse(EXAMPLE).unwrap();
@@ -101,4 +165,18 @@ mod tests {
         let result = part_2(&input);
         assert_eq!(result, 2);
     }
+
+    #[test]
+    fn test_groups() {
+        let input = parse(EXAMPLE).unwrap();
+        let result = groups(&input);
+        assert_eq!(result, [&[0, 2, 3, 4, 5, 6][..], &[1][..]]);
+    }
+
+    #[test]
+    fn test_groups_empty() {
+        let input = parse("").unwrap();
+        let result = groups(&input);
+        assert_eq!(result, Vec::<Vec<u16>>::new());
+    }
 }
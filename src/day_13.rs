@@ -12,12 +12,24 @@ enum ParseError {
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 struct Layer {
     depth: u64,
     range: u64,
 }
 
 impl Layer {
+    /// Number of picoseconds before the scanner is back at the top.
+    ///
+    /// A scanner with range 1 never leaves the top, so every time step is a multiple of its period.
+    const fn period(&self) -> u64 {
+        if self.range == 1 {
+            1
+        } else {
+            2 * self.range - 2
+        }
+    }
+
     const fn severity(&self) -> u64 {
         if self.is_safe_delay(0) {
             0
@@ -27,7 +39,17 @@ impl Layer {
     }
 
     const fn is_safe_delay(&self, delay: u64) -> bool {
-        !(self.depth + delay).is_multiple_of(2 * self.range - 2)
+        !(self.depth + delay).is_multiple_of(self.period())
+    }
+
+    /// Row of the scanner at absolute time `time`, with 0 being the top.
+    const fn scanner_position(&self, time: u64) -> u64 {
+        let phase = time % self.period();
+        if phase < self.range {
+            phase
+        } else {
+            self.period() - phase
+        }
     }
 }
 
@@ -55,14 +77,51 @@ fn part_1(layers: &[Layer]) -> u64 {
 
 #[aoc(day13, part2)]
 fn part_2(layers: &[Layer]) -> u64 {
-    (0..10_000_000)
-        .find(|&delay| layers.iter().all(|l| l.is_safe_delay(delay)))
-        .unwrap()
+    smallest_safe_delay(layers, 10_000_000).expect("No safe delay within the search limit")
+}
+
+/// The smallest delay below `max_delay` that passes every scanner, if there is one.
+fn smallest_safe_delay(layers: &[Layer], max_delay: u64) -> Option<u64> {
+    (0..max_delay).find(|&delay| layers.iter().all(|l| l.is_safe_delay(delay)))
+}
+
+/// The range of each layer, indexed by depth, with `None` for depths without a scanner.
+#[allow(unused)]
+fn dense_layers(layers: &[Layer]) -> Vec<Option<u64>> {
+    let Some(max_depth) = layers.iter().map(|l| l.depth).max() else {
+        return Vec::new();
+    };
+    let mut dense = vec![None; usize::try_from(max_depth).unwrap() + 1];
+    for layer in layers {
+        dense[usize::try_from(layer.depth).unwrap()] = Some(layer.range);
+    }
+    dense
+}
+
+/// Picture of the firewall at absolute time `time`, indexed by `[row][depth]`. Each scanner is
+/// drawn as `S` in its range of `.` cells, and everything else is blank. Rows below `max_range`
+/// are cut off.
+#[allow(unused)]
+fn frame_at(layers: &[Layer], time: u64, max_range: u64) -> Vec<Vec<char>> {
+    let depths = layers.iter().map(|l| l.depth + 1).max().unwrap_or(0);
+    let mut frame =
+        vec![vec![' '; usize::try_from(depths).unwrap()]; usize::try_from(max_range).unwrap()];
+    for layer in layers {
+        let col = usize::try_from(layer.depth).unwrap();
+        let scanner = layer.scanner_position(time);
+        for (r, row) in (0..layer.range).zip(&mut frame) {
+            row[col] = if r == scanner { 'S' } else { '.' };
+        }
+    }
+    frame
 }
 
+solver!(parse; 1 => part_1, 2 => part_2);
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use test_case::test_case;
 
     const EXAMPLE: &str = "\
         0: 3\n\
@@ -86,6 +145,30 @@ mod tests {
         );
     }
 
+    #[test_case(1 => 1)]
+    #[test_case(2 => 2)]
+    #[test_case(3 => 4)]
+    #[test_case(4 => 6)]
+    #[test_case(10 => 18)]
+    fn test_period(range: u64) -> u64 {
+        Layer { depth: 0, range }.period()
+    }
+
+    #[test]
+    fn test_range_one_always_caught() {
+        let layer = Layer { depth: 3, range: 1 };
+        assert!((0..10).all(|delay| !layer.is_safe_delay(delay)));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_round_trip() {
+        let layer = Layer { depth: 4, range: 6 };
+        let json = serde_json::to_string(&layer).unwrap();
+        assert_eq!(json, r#"{"depth":4,"range":6}"#);
+        assert_eq!(serde_json::from_str::<Layer>(&json).unwrap(), layer);
+    }
+
     #[test]
     fn test_part_1() {
         let layers = parse(EXAMPLE).unwrap();
@@ -99,4 +182,60 @@ mod tests {
         let result = part_2(&layers);
         assert_eq!(result, 10);
     }
+
+    #[test]
+    fn test_dense_layers() {
+        let layers = parse(EXAMPLE).unwrap();
+        let result = dense_layers(&layers);
+        assert_eq!(
+            result,
+            [Some(3), Some(2), None, None, Some(4), None, Some(4)]
+        );
+        assert_eq!(dense_layers(&[]), []);
+    }
+
+    #[test]
+    fn test_smallest_safe_delay() {
+        let layers = parse(EXAMPLE).unwrap();
+        assert_eq!(smallest_safe_delay(&layers, 11), Some(10));
+        assert_eq!(smallest_safe_delay(&layers, 10), None);
+
+        // Even delays are caught at depth 0, odd delays at depth 1
+        let no_gaps = [Layer { depth: 0, range: 2 }, Layer { depth: 1, range: 2 }];
+        assert_eq!(smallest_safe_delay(&no_gaps, 1000), None);
+        assert_eq!(smallest_safe_delay(&[Layer { depth: 5, range: 1 }], 1000), None);
+    }
+
+    #[test_case(0 => 0)]
+    #[test_case(1 => 1)]
+    #[test_case(3 => 3)]
+    #[test_case(4 => 2)]
+    #[test_case(6 => 0)]
+    fn test_scanner_position(time: u64) -> u64 {
+        Layer { depth: 0, range: 4 }.scanner_position(time)
+    }
+
+    #[test]
+    fn test_frame_at() {
+        let layers = parse(EXAMPLE).unwrap();
+        let frame = frame_at(&layers, 0, 4);
+        assert_eq!(frame.len(), 4);
+        assert!(frame.iter().all(|row| row.len() == 7));
+        for layer in &layers {
+            let col = usize::try_from(layer.depth).unwrap();
+            assert_eq!(frame[0][col], 'S');
+            assert!(frame[1..].iter().all(|row| row[col] != 'S'));
+        }
+        let rendered = |frame: Vec<Vec<char>>| {
+            frame
+                .into_iter()
+                .map(String::from_iter)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(rendered(frame), ["SS  S S", "..  . .", ".   . .", "    . ."]);
+        assert_eq!(
+            rendered(frame_at(&layers, 3, 3)),
+            ["..  . .", "SS  . .", ".   . ."]
+        );
+    }
 }
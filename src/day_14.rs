@@ -1,25 +1,62 @@
-use crate::utils::{KnotHasher, UnionFind};
+use crate::utils::{Grid, KnotHasher, UnionFind};
+use std::collections::VecDeque;
 use std::fmt::Write;
+use std::ops::Range;
 
-#[aoc(day14, part1)]
-fn part_1(input: &str) -> u32 {
+/// All 128 row digests of the disk, reusing one hasher and key buffer for every row.
+fn disk_hashes(input: &str) -> [[u8; 16]; 128] {
     let mut key = String::with_capacity(input.len() + 4);
     key.push_str(input);
     key.push('-');
     let mut hasher = KnotHasher::<256>::new(&[]);
     let prefix_len = key.len();
-    let mut total_bits = 0;
-    for r in 0..128 {
+    let mut digests = [[0; 16]; 128];
+    for (r, row_hash) in digests.iter_mut().enumerate() {
         key.truncate(prefix_len);
         write!(&mut key, "{r}").unwrap();
         hasher.reset(key.as_bytes());
         hasher.scramble_full();
-
-        let mut row_hash = [0; 16];
-        hasher.write_hash(&mut row_hash);
-        total_bits += row_hash.into_iter().map(u8::count_ones).sum::<u32>();
+        hasher.write_hash(row_hash);
     }
-    total_bits
+    digests
+}
+
+#[aoc(day14, part1)]
+fn part_1(input: &str) -> u32 {
+    disk_hashes(input)
+        .iter()
+        .flatten()
+        .map(|b| b.count_ones())
+        .sum()
+}
+
+/// Like [`part_1`], but only counts the used squares in `rows`.
+#[allow(unused)]
+fn used_squares_in_region(input: &str, rows: Range<usize>) -> u32 {
+    disk_hashes(input)[rows]
+        .iter()
+        .flatten()
+        .map(|b| b.count_ones())
+        .sum()
+}
+
+#[cfg(feature = "parallel")]
+#[aoc(day14, part1, Parallel)]
+fn part_1_parallel(input: &str) -> u32 {
+    use rayon::prelude::*;
+
+    (0..128)
+        .into_par_iter()
+        .map(|r| {
+            let key = format!("{input}-{r}");
+            let mut hasher = KnotHasher::<256>::new(key.as_bytes());
+            hasher.scramble_full();
+
+            let mut row_hash = [0; 16];
+            hasher.write_hash(&mut row_hash);
+            row_hash.into_iter().map(u8::count_ones).sum::<u32>()
+        })
+        .sum()
 }
 
 #[aoc(day14, part2)]
@@ -27,23 +64,11 @@ fn part_2(input: &str) -> usize {
     const OUTSIDE: usize = 128 * 128;
     const STRIDE: usize = 128;
     let mut uf = UnionFind::new(128 * 128 + 1);
-    let mut key = String::with_capacity(input.len() + 4);
-    key.push_str(input);
-    key.push('-');
-    let mut hasher = KnotHasher::<256>::new(&[]);
-    let prefix_len = key.len();
-    let mut prev_hash = None::<[u8; 16]>;
-    for r in 0..128 {
-        key.truncate(prefix_len);
-        write!(&mut key, "{r}").unwrap();
-        hasher.reset(key.as_bytes());
-        hasher.scramble_full();
-
-        let mut row_hash = [0; 16];
-        hasher.write_hash(&mut row_hash);
-
+    let hashes = disk_hashes(input);
+    let mut prev_hash = None::<&[u8; 16]>;
+    for (r, row_hash) in hashes.iter().enumerate() {
         let mut prev_cell = false;
-        for (c, cell) in BitIterator::new(&row_hash).enumerate() {
+        for (c, cell) in BitIterator::new(row_hash).enumerate() {
             if !cell {
                 uf.union(OUTSIDE, r * STRIDE + c);
             } else if prev_cell {
@@ -52,8 +77,8 @@ fn part_2(input: &str) -> usize {
             prev_cell = cell;
         }
         if let Some(above) = prev_hash {
-            for (c, (cell1, cell2)) in BitIterator::new(&above)
-                .zip(BitIterator::new(&row_hash))
+            for (c, (cell1, cell2)) in BitIterator::new(above)
+                .zip(BitIterator::new(row_hash))
                 .enumerate()
             {
                 if cell1 && cell2 {
@@ -66,6 +91,44 @@ fn part_2(input: &str) -> usize {
     uf.num_groups() - 1
 }
 
+/// Like [`part_2`], but counts the regions with a flood fill over the used squares.
+#[aoc(day14, part2, Flood)]
+fn region_count_flood(input: &str) -> usize {
+    let mut used = Grid::<bool>::new(128, 128);
+    for (r, row_hash) in disk_hashes(input).iter().enumerate() {
+        for (c, cell) in BitIterator::new(row_hash).enumerate() {
+            used[(r, c)] = cell;
+        }
+    }
+    let mut regions = 0;
+    let mut queue = VecDeque::new();
+    for start in (0..128).flat_map(|r| (0..128).map(move |c| (r, c))) {
+        if !used[start] {
+            continue;
+        }
+        regions += 1;
+        used[start] = false;
+        queue.push_back(start);
+        while let Some((r, c)) = queue.pop_front() {
+            let neighbors = [
+                r.checked_sub(1).map(|r| (r, c)),
+                c.checked_sub(1).map(|c| (r, c)),
+                Some((r + 1, c)),
+                Some((r, c + 1)),
+            ];
+            for pos in neighbors.into_iter().flatten() {
+                if let Some(cell) = used.get_mut(pos)
+                    && *cell
+                {
+                    *cell = false;
+                    queue.push_back(pos);
+                }
+            }
+        }
+    }
+    regions
+}
+
 #[derive(Debug, Clone)]
 struct BitIterator<'a> {
     bytes: &'a [u8],
@@ -100,6 +163,8 @@ impl Iterator for BitIterator<'_> {
     }
 }
 
+solver!(raw; 1 => part_1, 2 => part_2);
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -125,9 +190,38 @@ mod tests {
         assert_eq!(result, 8108);
     }
 
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn test_part_1_parallel() {
+        assert_eq!(part_1_parallel("flqrgnkx"), part_1("flqrgnkx"));
+    }
+
     #[test]
     fn test_part_2() {
         let result = part_2("flqrgnkx");
         assert_eq!(result, 1242);
     }
+
+    #[test]
+    fn test_disk_hashes() {
+        let hashes = disk_hashes("flqrgnkx");
+        let bits: u32 = hashes.iter().flatten().map(|b| b.count_ones()).sum();
+        assert_eq!(bits, 8108);
+        assert_eq!(hashes[0][0] >> 4, 0xd);
+    }
+
+    #[test]
+    fn test_region_count_flood() {
+        assert_eq!(region_count_flood("flqrgnkx"), 1242);
+        assert_eq!(region_count_flood("AoC 2017"), part_2("AoC 2017"));
+    }
+
+    #[test]
+    fn test_used_squares_in_region() {
+        let parts = [0..8, 8..64, 64..100, 100..128]
+            .map(|rows| used_squares_in_region("flqrgnkx", rows));
+        assert_eq!(parts.iter().sum::<u32>(), 8108);
+        assert_eq!(used_squares_in_region("flqrgnkx", 0..128), 8108);
+        assert_eq!(used_squares_in_region("flqrgnkx", 5..5), 0);
+    }
 }
@@ -3,20 +3,31 @@ use std::str::FromStr;
 
 use thiserror::Error;
 
+/// The modulus used by the puzzle's generators, `2^31 - 1`.
+const MODULUS: u64 = 2_147_483_647;
+
 struct Generator {
     last_value: u64,
     multiply: u64,
+    modulus: u64,
     filter: Option<u64>,
 }
 
 impl Generator {
-    pub const fn new(last_value: u64, multiply: u64, filter: Option<u64>) -> Self {
+    pub const fn new(last_value: u64, multiply: u64, modulus: u64, filter: Option<u64>) -> Self {
         Self {
             last_value,
             multiply,
+            modulus,
             filter,
         }
     }
+
+    /// Restarts the sequence from a new seed, keeping the multiplier, modulus and filter.
+    #[allow(unused)]
+    pub const fn reseed(&mut self, value: u64) {
+        self.last_value = value;
+    }
 }
 
 impl Iterator for Generator {
@@ -24,7 +35,7 @@ impl Iterator for Generator {
 
     fn next(&mut self) -> Option<Self::Item> {
         loop {
-            self.last_value = (self.last_value * self.multiply) % 2_147_483_647;
+            self.last_value = (self.last_value * self.multiply) % self.modulus;
             if self
                 .filter
                 .is_none_or(|multiple_of| self.last_value.is_multiple_of(multiple_of))
@@ -83,10 +94,7 @@ fn parse(input: &str) -> Result<Input, ParseError> {
 
 #[aoc(day15, part1)]
 fn part_1(input: &Input) -> usize {
-    let generator_a = Generator::new(input.generator_a, 16_807, None);
-    let generator_b = Generator::new(input.generator_b, 48_271, None);
-    generator_a
-        .zip(generator_b)
+    pair_stream(input, (None, None))
         .take(40_000_000)
         .filter(|(a, b)| (a ^ b).trailing_zeros() >= 16)
         .count()
@@ -94,19 +102,95 @@ fn part_1(input: &Input) -> usize {
 
 #[aoc(day15, part2)]
 fn part_2(input: &Input) -> usize {
-    let generator_a = Generator::new(input.generator_a, 16_807, Some(4));
-    let generator_b = Generator::new(input.generator_b, 48_271, Some(8));
-    generator_a
-        .zip(generator_b)
+    pair_stream(input, (Some(4), Some(8)))
         .take(5_000_000)
         .filter(|(a, b)| (a ^ b).trailing_zeros() >= 16)
         .count()
 }
 
+/// Values from generator A and B, paired up after each generator's filter.
+fn pair_stream(
+    input: &Input,
+    (filter_a, filter_b): (Option<u64>, Option<u64>),
+) -> impl Iterator<Item = (u64, u64)> {
+    let generator_a = Generator::new(input.generator_a, 16_807, MODULUS, filter_a);
+    let generator_b = Generator::new(input.generator_b, 48_271, MODULUS, filter_b);
+    generator_a.zip(generator_b)
+}
+
+/// Counts the rounds where the lowest `bits` bits of every generator agree.
+#[allow(unused)]
+fn judge_n(generators: &mut [Generator], rounds: usize, bits: u32) -> usize {
+    let mask = 1_u64.checked_shl(bits).map_or(u64::MAX, |bit| bit - 1);
+    let mut count = 0;
+    for _ in 0..rounds {
+        let mut expected = None;
+        let mut all_agree = true;
+        // Every generator has to advance, even after a mismatch
+        for generator in generators.iter_mut() {
+            let value = generator.next().unwrap() & mask;
+            all_agree &= *expected.get_or_insert(value) == value;
+        }
+        if all_agree {
+            count += 1;
+        }
+    }
+    count
+}
+
+solver!(parse; 1 => part_1, 2 => part_2);
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_pair_stream() {
+        let input = Input {
+            generator_a: 65,
+            generator_b: 8921,
+        };
+        let pairs = pair_stream(&input, (None, None)).take(5).collect::<Vec<_>>();
+        assert_eq!(pairs[0], (1_092_455, 430_625_591));
+        let matches = pairs
+            .iter()
+            .map(|(a, b)| a & 0xFFFF == b & 0xFFFF)
+            .collect::<Vec<_>>();
+        assert_eq!(matches, [false, false, true, false, false]);
+    }
+
+    #[test]
+    fn test_judge_n() {
+        let mut two = [
+            Generator::new(65, 16_807, MODULUS, None),
+            Generator::new(8921, 48_271, MODULUS, None),
+        ];
+        assert_eq!(judge_n(&mut two, 5, 16), 1);
+
+        // A third generator identical to A does not change the outcome
+        let mut three = [
+            Generator::new(65, 16_807, MODULUS, None),
+            Generator::new(8921, 48_271, MODULUS, None),
+            Generator::new(65, 16_807, MODULUS, None),
+        ];
+        assert_eq!(judge_n(&mut three, 5, 16), 1);
+
+        let mut same = [
+            Generator::new(7, 3, MODULUS, None),
+            Generator::new(7, 3, MODULUS, None),
+            Generator::new(7, 3, MODULUS, None),
+        ];
+        assert_eq!(judge_n(&mut same, 10, 64), 10);
+
+        // With zero bits everything agrees
+        let mut differing = [
+            Generator::new(1, 2, MODULUS, None),
+            Generator::new(3, 4, MODULUS, None),
+            Generator::new(5, 6, MODULUS, None),
+        ];
+        assert_eq!(judge_n(&mut differing, 10, 0), 10);
+    }
+
     #[test]
     fn test_part_1() {
         let input = Input {
@@ -125,4 +209,26 @@ mod tests {
         let result = part_2(&input);
         assert_eq!(result, 309);
     }
+
+    #[test]
+    fn test_custom_modulus() {
+        let generator = Generator::new(1, 3, 7, None);
+        let values = generator.take(7).collect::<Vec<_>>();
+        assert_eq!(values, [3, 2, 6, 4, 5, 1, 3]);
+        let generator = Generator::new(1, 3, 7, Some(2));
+        let values = generator.take(4).collect::<Vec<_>>();
+        assert_eq!(values, [2, 6, 4, 2]);
+    }
+
+    #[test]
+    fn test_reseed() {
+        let mut generator = Generator::new(65, 16_807, MODULUS, Some(4));
+        let first = generator.by_ref().take(5).collect::<Vec<_>>();
+        generator.reseed(65);
+        let second = generator.by_ref().take(5).collect::<Vec<_>>();
+        assert_eq!(first, second);
+        assert_eq!(first[0], 1_352_636_452);
+        generator.reseed(8921);
+        assert_eq!(generator.next(), Generator::new(8921, 16_807, MODULUS, Some(4)).next());
+    }
 }
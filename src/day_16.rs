@@ -55,6 +55,20 @@ impl Name {
             Self::P,
         ]
     }
+
+    #[allow(unused)]
+    pub const fn from_index(index: u8) -> Option<Self> {
+        let all = Self::all();
+        if (index as usize) < all.len() {
+            Some(all[index as usize])
+        } else {
+            None
+        }
+    }
+
+    pub const fn to_char(self) -> char {
+        (self as u8 + b'a') as char
+    }
 }
 
 impl FromStr for Name {
@@ -128,7 +142,16 @@ fn part_1(instructions: &[Instruction]) -> String {
 }
 
 fn slow_dance<const N: usize>(instructions: &[Instruction]) -> String {
-    let mut programs: [Name; N] = Name::all()[..N].try_into().unwrap();
+    let start: [Name; N] = Name::all()[..N].try_into().unwrap();
+    slow_dance_from(start, instructions)
+        .into_iter()
+        .map(Name::to_char)
+        .collect()
+}
+
+/// Performs the dance once, starting from an arbitrary arrangement.
+fn slow_dance_from<const N: usize>(start: [Name; N], instructions: &[Instruction]) -> [Name; N] {
+    let mut programs = start;
     for &instr in instructions {
         match instr {
             Instruction::Spin(k) => programs.rotate_right(k),
@@ -140,7 +163,7 @@ fn slow_dance<const N: usize>(instructions: &[Instruction]) -> String {
             }
         }
     }
-    unsafe { String::from_utf8_unchecked(programs.map(|p| p as u8 + b'a').to_vec()) }
+    programs
 }
 
 #[aoc(day16, part2)]
@@ -150,32 +173,59 @@ fn part_2(instructions: &[Instruction]) -> String {
 }
 
 fn fast_dance<const N: usize>(instructions: &[Instruction], times: u32) -> String {
-    let all_names: [Name; N] = Name::all()[..N].try_into().unwrap();
+    Dance::<N>::new(instructions).after(times)
+}
 
-    let mut program = all_names;
-    for &instr in instructions {
-        match instr {
-            Instruction::Spin(k) => program.rotate_right(k),
-            Instruction::Exchange(a, b) => program.swap(a, b),
-            Instruction::Partner(..) => {}
+/// The effect of one dance, split into the moves by position (spin and exchange) and the moves by
+/// name (partner), which can be repeated independently of each other.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Dance<const N: usize> {
+    position_permutation: [usize; N],
+    value_permutation: [Name; N],
+}
+
+impl<const N: usize> Dance<N> {
+    fn new(instructions: &[Instruction]) -> Self {
+        let all_names: [Name; N] = Name::all()[..N].try_into().unwrap();
+
+        let mut program = all_names;
+        for &instr in instructions {
+            match instr {
+                Instruction::Spin(k) => program.rotate_right(k),
+                Instruction::Exchange(a, b) => program.swap(a, b),
+                Instruction::Partner(..) => {}
+            }
         }
-    }
-    let position_permutation = all_names.map(|n| program.iter().position(|&x| x == n).unwrap());
-    let position_permutation = power_permutation(position_permutation, times);
+        // Where each position takes its program from
+        let position_permutation = program.map(usize::from);
 
-    let mut program = all_names;
-    for &instr in instructions {
-        if let Instruction::Partner(a, b) = instr {
-            let a = program.iter().position(|&p| p == a).unwrap();
-            let b = program.iter().position(|&p| p == b).unwrap();
-            program.swap(a, b);
+        let mut program = all_names;
+        for &instr in instructions {
+            if let Instruction::Partner(a, b) = instr {
+                let a = program.iter().position(|&p| p == a).unwrap();
+                let b = program.iter().position(|&p| p == b).unwrap();
+                program.swap(a, b);
+            }
+        }
+
+        Self {
+            position_permutation,
+            value_permutation: program,
         }
     }
-    let value_permutation = power_permutation(program, times);
 
-    let result = position_permutation.map(|x| value_permutation[x]);
+    /// The arrangement after dancing `times` times, starting from `a`, `b`, `c`, ...
+    fn after(&self, times: u32) -> String {
+        if times == 0 {
+            return Name::all()[..N].iter().map(|&n| n.to_char()).collect();
+        }
+        let position_permutation = power_permutation(self.position_permutation, times);
+        let value_permutation = power_permutation(self.value_permutation, times);
 
-    unsafe { String::from_utf8_unchecked(result.map(|p| p as u8 + b'a').to_vec()) }
+        let result = position_permutation.map(|x| value_permutation[x]);
+
+        result.into_iter().map(Name::to_char).collect()
+    }
 }
 
 fn power_permutation<T: Copy + Into<usize>, const N: usize>(
@@ -214,6 +264,8 @@ fn multiply_permutations<T: Copy + Into<usize>, const N: usize>(
     permutation1.map(|x| permutation2[x.into()])
 }
 
+solver!(parse; 1 => part_1, 2 => part_2);
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -233,6 +285,13 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_name_from_index() {
+        assert_eq!(Name::from_index(0).map(Name::to_char), Some('a'));
+        assert_eq!(Name::from_index(15).map(Name::to_char), Some('p'));
+        assert_eq!(Name::from_index(16), None);
+    }
+
     #[test]
     fn test_slow_dance() {
         let instructions = parse(EXAMPLE).unwrap();
@@ -246,4 +305,31 @@ mod tests {
         let result = fast_dance::<5>(&instructions, 2);
         assert_eq!(result, "ceadb");
     }
+
+    #[test]
+    fn test_slow_dance_from() {
+        let instructions = parse(EXAMPLE).unwrap();
+        let start: [Name; 5] = Name::all()[..5].try_into().unwrap();
+        let once = slow_dance_from(start, &instructions);
+        assert_eq!(once, [Name::B, Name::A, Name::E, Name::D, Name::C]);
+        let twice = slow_dance_from(once, &instructions);
+        let twice_str: String = twice.into_iter().map(Name::to_char).collect();
+        assert_eq!(twice_str, fast_dance::<5>(&instructions, 2));
+        assert_eq!(twice_str, "ceadb");
+    }
+
+    #[test]
+    fn test_dance_after() {
+        let instructions = parse(EXAMPLE).unwrap();
+        let dance = Dance::<5>::new(&instructions);
+        assert_eq!(dance.after(2), fast_dance::<5>(&instructions, 2));
+        assert_eq!(dance.after(1), slow_dance::<5>(&instructions));
+        assert_eq!(dance.after(0), "abcde");
+        let mut arrangement: [Name; 5] = Name::all()[..5].try_into().unwrap();
+        for times in 1..=12 {
+            arrangement = slow_dance_from(arrangement, &instructions);
+            let expected: String = arrangement.into_iter().map(Name::to_char).collect();
+            assert_eq!(dance.after(times), expected, "{times}");
+        }
+    }
 }
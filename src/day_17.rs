@@ -21,17 +21,49 @@ fn part_1(&step: &usize) -> usize {
 #[aoc(day17, part2)]
 #[expect(clippy::trivially_copy_pass_by_ref, reason = "AOC lib")]
 fn part_2(&step: &usize) -> usize {
+    value_after_zero(step, 50_000_000)
+}
+
+#[aoc(day17, part2, Fast)]
+#[expect(clippy::trivially_copy_pass_by_ref, reason = "AOC lib")]
+fn part_2_fast(&step: &usize) -> usize {
+    value_after_zero_fast(step, 50_000_000)
+}
+
+/// The value directly after `0`, after inserting `1..=count`.
+fn value_after_zero(step: usize, count: usize) -> usize {
+    let mut pos = 0;
+    let mut value_after_zero = 0;
+    for t in 1..=count {
+        pos = (pos + step) % t + 1;
+        if pos == 1 {
+            value_after_zero = t;
+        }
+    }
+    value_after_zero
+}
+
+/// Like [`value_after_zero`], but skips over insertions that don't wrap around, since those can
+/// never land directly after `0`.
+fn value_after_zero_fast(step: usize, count: usize) -> usize {
     let mut pos = 0;
     let mut value_after_zero = 0;
-    for t in 1..=50_000_000 {
+    let mut t = 1;
+    while t <= count {
         pos = (pos + step) % t + 1;
         if pos == 1 {
             value_after_zero = t;
         }
+        // The next `skip` insertions each move `step + 1` forward without wrapping.
+        let skip = (t - pos).checked_div(step).unwrap_or(count);
+        pos += skip * (step + 1);
+        t += skip + 1;
     }
     value_after_zero
 }
 
+solver!(parse; 1 => part_1, 2 => part_2);
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -41,4 +73,18 @@ mod tests {
         let result = part_1(&3);
         assert_eq!(result, 638);
     }
+
+    #[test]
+    fn test_value_after_zero_fast() {
+        for step in 0..50 {
+            for count in [0, 1, 2, 3, 10, 100, 2017] {
+                assert_eq!(
+                    value_after_zero_fast(step, count),
+                    value_after_zero(step, count),
+                    "step {step}, count {count}"
+                );
+            }
+        }
+        assert_eq!(value_after_zero(3, 9), 9);
+    }
 }
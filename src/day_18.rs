@@ -1,10 +1,13 @@
-use std::collections::VecDeque;
+use std::collections::{HashSet, VecDeque};
+use std::fmt::{Display, Write};
 use std::num::ParseIntError;
 use std::ops::{Index, IndexMut};
 use std::str::FromStr;
 
 use thiserror::Error;
 
+use crate::common;
+
 #[derive(Debug, Error)]
 enum ParseError {
     #[error("Syntax error")]
@@ -44,6 +47,18 @@ impl Reg {
             _ => return Err(ParseError::InvalidRegister),
         })
     }
+
+    const fn letter(self) -> u8 {
+        match self {
+            Self::A => b'a',
+            Self::B => b'b',
+            Self::C => b'c',
+            Self::D => b'd',
+            Self::F => b'f',
+            Self::I => b'i',
+            Self::P => b'p',
+        }
+    }
 }
 
 impl FromStr for Reg {
@@ -58,6 +73,12 @@ impl FromStr for Reg {
     }
 }
 
+impl Display for Reg {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.letter() as char)
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum RegOrValue {
     Reg(Reg),
@@ -75,6 +96,15 @@ impl FromStr for RegOrValue {
     }
 }
 
+impl Display for RegOrValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Reg(reg) => reg.fmt(f),
+            Self::Value(v) => v.fmt(f),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum BinOp {
     Set,
@@ -94,6 +124,17 @@ impl BinOp {
     }
 }
 
+impl Display for BinOp {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Set => f.write_str("set"),
+            Self::Add => f.write_str("add"),
+            Self::Mul => f.write_str("mul"),
+            Self::Mod => f.write_str("mod"),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum Instruction {
     Snd(RegOrValue),
@@ -131,6 +172,49 @@ impl FromStr for Instruction {
     }
 }
 
+impl Display for Instruction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Snd(value) => write!(f, "snd {value}"),
+            Self::BinOp(op, reg, value) => write!(f, "{op} {reg} {value}"),
+            Self::Rcv(reg) => write!(f, "rcv {reg}"),
+            Self::Jgz(check, delta) => write!(f, "jgz {check} {delta}"),
+        }
+    }
+}
+
+/// The same instruction in the instruction set shared with day 23.
+#[allow(unused)]
+fn to_common(instr: Instruction) -> common::Instruction {
+    let reg = |reg: Reg| common::Reg::new(reg.letter()).unwrap();
+    let value = |value| match value {
+        RegOrValue::Reg(r) => common::RegOrValue::Reg(reg(r)),
+        RegOrValue::Value(v) => common::RegOrValue::Value(v),
+    };
+    match instr {
+        Instruction::Snd(src) => common::Instruction::Snd(value(src)),
+        Instruction::Rcv(r) => common::Instruction::Rcv(reg(r)),
+        Instruction::BinOp(op, r, rhs) => {
+            let (op, rhs) = match (op, rhs) {
+                // Day 23 only has `sub`, so literal additions take the same form
+                (BinOp::Add, RegOrValue::Value(v)) if v != i64::MIN => {
+                    (common::BinOp::Sub, RegOrValue::Value(-v))
+                }
+                (BinOp::Set, _) => (common::BinOp::Set, rhs),
+                (BinOp::Add, _) => (common::BinOp::Add, rhs),
+                (BinOp::Mul, _) => (common::BinOp::Mul, rhs),
+                (BinOp::Mod, _) => (common::BinOp::Mod, rhs),
+            };
+            common::Instruction::BinOp(op, reg(r), value(rhs))
+        }
+        Instruction::Jgz(check, delta) => common::Instruction::Jump(
+            common::Condition::GreaterThanZero,
+            value(check),
+            value(delta),
+        ),
+    }
+}
+
 #[aoc_generator(day18)]
 fn parse(input: &str) -> Result<Vec<Instruction>, ParseError> {
     input.lines().map(str::parse).collect()
@@ -138,9 +222,16 @@ fn parse(input: &str) -> Result<Vec<Instruction>, ParseError> {
 
 #[aoc(day18, part1)]
 fn part_1(instructions: &[Instruction]) -> i64 {
+    let mut machine = Machine::new(instructions, true);
+    machine.run_until_recover().unwrap_or(0)
+}
+
+/// All sounds played, in order, up until the first `rcv` with a nonzero argument.
+#[allow(unused)]
+fn run_collecting_sounds(instructions: &[Instruction]) -> Vec<i64> {
     let mut machine = Machine::new(instructions, true);
     machine.run();
-    machine.output_queue.pop_back().unwrap_or(0)
+    machine.output_queue.into()
 }
 
 #[aoc(day18, part2)]
@@ -169,6 +260,16 @@ fn part_2(instructions: &[Instruction]) -> usize {
     }
 }
 
+#[allow(unused)]
+fn solve_part1_str(input: &str) -> Result<i64, ParseError> {
+    Ok(part_1(&parse(input)?))
+}
+
+#[allow(unused)]
+fn solve_part2_str(input: &str) -> Result<usize, ParseError> {
+    Ok(part_2(&parse(input)?))
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum State {
     Pending,
@@ -176,6 +277,14 @@ enum State {
     Stopped,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum HaltReason {
+    /// A `jgz` tried to jump to this instruction index.
+    JumpOutOfBounds(isize),
+    /// Ran past the last instruction.
+    EndOfProgram,
+}
+
 #[derive(Debug, Clone)]
 struct Machine<'a> {
     instructions: &'a [Instruction],
@@ -186,6 +295,7 @@ struct Machine<'a> {
     output_queue: VecDeque<i64>,
     output_count: usize,
     input_queue: VecDeque<i64>,
+    last_halt_reason: Option<HaltReason>,
 }
 
 impl<'a> Machine<'a> {
@@ -199,6 +309,7 @@ impl<'a> Machine<'a> {
             output_queue: VecDeque::new(),
             output_count: 0,
             input_queue: VecDeque::new(),
+            last_halt_reason: None,
         }
     }
 
@@ -218,6 +329,7 @@ impl<'a> Machine<'a> {
         }
         let Some(&instr) = self.instructions.get(self.ip) else {
             self.state = State::Stopped;
+            self.last_halt_reason = Some(HaltReason::EndOfProgram);
             return;
         };
         match instr {
@@ -241,15 +353,16 @@ impl<'a> Machine<'a> {
             }
             Instruction::Jgz(check, delta) => {
                 if self.get_value(check) > 0 {
-                    if let Some(new_ip) = self
-                        .ip
-                        .checked_add_signed(isize::try_from(self.get_value(delta)).unwrap())
+                    let delta = isize::try_from(self.get_value(delta)).unwrap();
+                    if let Some(new_ip) = self.ip.checked_add_signed(delta)
                         && new_ip < self.instructions.len()
                     {
                         self.ip = new_ip;
                         return;
                     }
                     self.state = State::Stopped;
+                    let target = isize::try_from(self.ip).unwrap().saturating_add(delta);
+                    self.last_halt_reason = Some(HaltReason::JumpOutOfBounds(target));
                 }
             }
         }
@@ -264,6 +377,67 @@ impl<'a> Machine<'a> {
             self.step();
         }
     }
+
+    /// Like [`Machine::run`], but when a `rcv` finds the input queue empty, asks `source` for the
+    /// next value instead of stopping. Returns once the machine halts, or `source` runs dry.
+    #[allow(unused)]
+    fn run_with_source<F>(&mut self, mut source: F)
+    where
+        F: FnMut() -> Option<i64>,
+    {
+        loop {
+            self.run();
+            if self.state != State::WaitingForInput {
+                return;
+            }
+            let Some(value) = source() else {
+                return;
+            };
+            self.input_queue.push_back(value);
+        }
+    }
+
+    /// Lists the instructions up to `window` lines around the current `ip`, marking the current
+    /// one with `>`.
+    #[allow(unused)]
+    fn context(&self, window: usize) -> String {
+        let start = self.ip.saturating_sub(window);
+        let end = self
+            .ip
+            .saturating_add(window)
+            .saturating_add(1)
+            .min(self.instructions.len());
+        let mut result = String::new();
+        for (i, instr) in self.instructions.iter().enumerate().take(end).skip(start) {
+            let marker = if i == self.ip { '>' } else { ' ' };
+            writeln!(result, "{i:3}) {marker} {instr}").unwrap();
+        }
+        result
+    }
+
+    /// Runs until the first `rcv` with a nonzero argument, and returns the last played sound.
+    /// Returns `None` if the program stops without recovering anything, or if it returns to an
+    /// earlier `(ip, registers)` state and would loop forever.
+    fn run_until_recover(&mut self) -> Option<i64> {
+        if self.state == State::WaitingForInput && !self.input_queue.is_empty() {
+            self.state = State::Pending;
+        }
+        // Every loop passes through a jump, so it is enough to remember the states at those.
+        let mut seen = HashSet::new();
+        while self.state == State::Pending {
+            if self.input_queue.is_empty()
+                && matches!(self.instructions.get(self.ip), Some(Instruction::Jgz(..)))
+                && !seen.insert((self.ip, self.registers))
+            {
+                return None;
+            }
+            self.step();
+        }
+        match self.state {
+            State::WaitingForInput => self.output_queue.back().copied(),
+            State::Pending | State::Stopped => None,
+        }
+    }
 }
 
 impl Index<Reg> for Machine<'_> {
@@ -280,6 +454,8 @@ impl IndexMut<Reg> for Machine<'_> {
     }
 }
 
+solver!(parse; 1 => part_1, 2 => part_2);
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -335,10 +511,124 @@ mod tests {
         assert_eq!(result, 4);
     }
 
+    #[test]
+    fn test_run_collecting_sounds() {
+        let instructions = parse(EXAMPLE1).unwrap();
+        let sounds = run_collecting_sounds(&instructions);
+        assert_eq!(sounds.last(), Some(&4));
+    }
+
+    #[test]
+    fn test_solve_str() {
+        assert_eq!(solve_part1_str(EXAMPLE1).unwrap(), 4);
+        assert_eq!(solve_part2_str(EXAMPLE2).unwrap(), 3);
+        assert!(solve_part1_str("nop").is_err());
+    }
+
+    #[test]
+    fn test_run_until_recover() {
+        let instructions = parse(EXAMPLE1).unwrap();
+        let mut machine = Machine::new(&instructions, true);
+        assert_eq!(machine.run_until_recover(), Some(4));
+    }
+
     #[test]
     fn test_part_2() {
         let instructions = parse(EXAMPLE2).unwrap();
         let result = part_2(&instructions);
         assert_eq!(result, 3);
     }
+
+    #[test]
+    fn test_display() {
+        for line in EXAMPLE1.lines().chain(EXAMPLE2.lines()) {
+            let instr: Instruction = line.parse().unwrap();
+            assert_eq!(instr.to_string(), line);
+        }
+    }
+
+    #[test]
+    fn test_context() {
+        let instructions = parse(EXAMPLE1).unwrap();
+        let mut machine = Machine::new(&instructions, true);
+        machine.run();
+        assert_eq!(machine.ip, 6);
+        assert_eq!(
+            machine.context(1),
+            "  5)   set a 0\n  6) > rcv a\n  7)   jgz a -1\n"
+        );
+        assert_eq!(machine.context(100).lines().count(), instructions.len());
+    }
+
+    #[test]
+    fn test_halt_reason() {
+        let run = |input| {
+            let instructions = parse(input).unwrap();
+            let mut machine = Machine::new(&instructions, true);
+            assert_eq!(machine.last_halt_reason, None);
+            machine.run();
+            assert_eq!(machine.state, State::Stopped);
+            machine.last_halt_reason
+        };
+        assert_eq!(run("set a 1\njgz a 5"), Some(HaltReason::JumpOutOfBounds(6)));
+        assert_eq!(run("set a 1\njgz a -2"), Some(HaltReason::JumpOutOfBounds(-1)));
+        assert_eq!(run("set a 1\nadd a 2"), Some(HaltReason::EndOfProgram));
+    }
+
+    #[test]
+    fn test_run_until_recover_loop() {
+        // `a` stays zero, so the `rcv` never triggers
+        let instructions = parse("set b 3\nsnd b\nrcv a\nmul a 2\njgz b -3").unwrap();
+        let mut machine = Machine::new(&instructions, true);
+        assert_eq!(machine.run_until_recover(), None);
+        assert_eq!(machine.state, State::Pending);
+
+        // A counting loop that eventually recovers is not mistaken for a cycle
+        let instructions = parse("set a 5\nsnd a\nadd a -1\njgz a -2\nset b 1\nrcv b").unwrap();
+        let mut machine = Machine::new(&instructions, true);
+        assert_eq!(machine.run_until_recover(), Some(1));
+    }
+
+    #[test]
+    fn test_run_with_source() {
+        let instructions = parse(EXAMPLE2).unwrap();
+        let reg = |ch| Reg::new(ch).unwrap();
+
+        let mut machine0 = Machine::new(&instructions, false);
+        machine0[reg(b'p')] = 0;
+        machine0.run();
+        let sent = machine0.output_queue.drain(..).collect::<Vec<_>>();
+        assert_eq!(sent, [1, 2, 0]);
+
+        let mut machine1 = Machine::new(&instructions, false);
+        machine1[reg(b'p')] = 1;
+        let mut values = sent.into_iter();
+        machine1.run_with_source(|| values.next());
+        assert_eq!(machine1.state, State::WaitingForInput);
+        assert_eq!(machine1.output_count, 3);
+        assert_eq!(
+            [b'a', b'b', b'c', b'd'].map(|ch| machine1[reg(ch)]),
+            [1, 2, 0, 0]
+        );
+
+        // The source is only asked when the queue is empty
+        machine1.input_queue.push_back(7);
+        machine1.run_with_source(|| panic!("queue was not empty"));
+        assert_eq!(machine1[reg(b'd')], 7);
+        assert_eq!(machine1.state, State::Stopped);
+    }
+
+    #[test]
+    fn test_to_common() {
+        let instructions = parse(EXAMPLE1).unwrap();
+        let converted = instructions.iter().copied().map(to_common).collect::<Vec<_>>();
+        assert_eq!(converted[1].to_string(), "sub a -2");
+        assert_eq!(converted[7].to_string(), "jgz a -1");
+        let mut machine = common::Machine::new(&converted);
+        assert_eq!(machine.run_until_recover(), Some(4));
+        assert_eq!(
+            Machine::new(&instructions, true).run_until_recover(),
+            Some(4)
+        );
+    }
 }
@@ -1,3 +1,5 @@
+use std::collections::HashSet;
+
 use thiserror::Error;
 
 use crate::utils::{Grid, GridParseError};
@@ -8,6 +10,12 @@ enum ParseError {
     InvalidTile,
 }
 
+#[derive(Debug, Error)]
+enum WalkError {
+    #[error("The path loops back on itself")]
+    Loop,
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum Tile {
     Empty,
@@ -32,6 +40,32 @@ impl TryFrom<u8> for Tile {
     }
 }
 
+impl Tile {
+    /// Inverse of `TryFrom<u8>`
+    #[allow(unused)]
+    const fn to_byte(self) -> u8 {
+        match self {
+            Self::Empty => b' ',
+            Self::Vertical => b'|',
+            Self::Horizontal => b'-',
+            Self::Corner => b'+',
+            Self::Letter(ch) => ch,
+        }
+    }
+}
+
+impl TryFrom<char> for Tile {
+    type Error = ParseError;
+
+    fn try_from(value: char) -> Result<Self, Self::Error> {
+        if value.is_ascii() {
+            Self::try_from(value as u8)
+        } else {
+            Err(ParseError::InvalidTile)
+        }
+    }
+}
+
 #[aoc_generator(day19)]
 fn parse(input: &str) -> Result<Grid<Tile>, GridParseError<ParseError>> {
     input.parse()
@@ -44,29 +78,66 @@ fn part_1(map: &Grid<Tile>) -> String {
         if let Tile::Letter(ch) = tile {
             found_letters.push(ch);
         }
-    });
+    })
+    .expect("The path should end");
     unsafe { String::from_utf8_unchecked(found_letters) }
 }
 
 #[aoc(day19, part2)]
 fn part_2(map: &Grid<Tile>) -> usize {
     let mut steps = 0;
-    walk_map(map, |_| steps += 1);
+    walk_map(map, |_| steps += 1).expect("The path should end");
     steps
 }
 
-fn walk_map<F>(map: &Grid<Tile>, mut visit: F)
+/// Number of steps along each straight part of the path, from the start to the first corner,
+/// between corners, and from the last corner to the end. Sums to one less than [`part_2`].
+#[allow(unused)]
+fn segment_lengths(map: &Grid<Tile>) -> Vec<usize> {
+    let mut segments = Vec::new();
+    let mut steps = None::<usize>;
+    walk_map(map, |tile| {
+        // The first tile is the start, not a step
+        let Some(steps) = steps.as_mut() else {
+            steps = Some(0);
+            return;
+        };
+        *steps += 1;
+        if tile == Tile::Corner {
+            segments.push(*steps);
+            *steps = 0;
+        }
+    })
+    .expect("The path should end");
+    if let Some(steps) = steps
+        && steps > 0
+    {
+        segments.push(steps);
+    }
+    segments
+}
+
+/// Follows the path from the top, visiting every tile along the way. Fails if the same step is
+/// taken twice, since the path would then never end.
+fn walk_map<F>(map: &Grid<Tile>, mut visit: F) -> Result<(), WalkError>
 where
     F: FnMut(Tile),
 {
-    let start = (0..map.cols())
-        .map(|c| (0, c))
-        .find(|&pos| map[pos] == Tile::Vertical)
-        .unwrap();
+    let start = (
+        0,
+        map.row(0)
+            .iter()
+            .position(|&tile| tile == Tile::Vertical)
+            .unwrap(),
+    );
     let mut came_from = start;
     visit(map[came_from]);
     let mut current = (start.0 + 1, start.1);
-    while map[current] != Tile::Empty {
+    let mut taken = HashSet::new();
+    while map.get(current).is_some_and(|&tile| tile != Tile::Empty) {
+        if !taken.insert((came_from, current)) {
+            return Err(WalkError::Loop);
+        }
         visit(map[current]);
         let next = match map[current] {
             Tile::Letter(..) | Tile::Vertical | Tile::Horizontal => {
@@ -76,18 +147,21 @@ where
             Tile::Corner => [
                 current.0.checked_sub(1).map(|r| (r, current.1)),
                 current.1.checked_sub(1).map(|c| (current.0, c)),
-                (current.0 + 1 < map.rows()).then_some((current.0 + 1, current.1)),
-                (current.1 + 1 < map.cols()).then_some((current.0, current.1 + 1)),
+                Some((current.0 + 1, current.1)),
+                Some((current.0, current.1 + 1)),
             ]
             .into_iter()
             .flatten()
-            .find(|&pos| map[pos] != Tile::Empty && pos != came_from)
+            .find(|&pos| pos != came_from && map.get(pos).is_some_and(|&t| t != Tile::Empty))
             .unwrap(),
         };
         (came_from, current) = (current, next);
     }
+    Ok(())
 }
 
+solver!(parse; 1 => part_1, 2 => part_2);
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -131,6 +205,33 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_find_letter() {
+        let map = parse(&EXAMPLE.replace('.', " ")).unwrap();
+        let pos = map.find_pos(|tile| matches!(tile, Tile::Letter(..)));
+        assert_eq!(pos, Some((2, 5)));
+        assert_eq!(map[pos.unwrap()], Tile::Letter(b'A'));
+    }
+
+    #[test]
+    fn test_map_is_empty() {
+        let map = parse(&EXAMPLE.replace('.', " ")).unwrap();
+        let is_empty = map.map(|&tile| tile == Tile::Empty);
+        assert_eq!(is_empty.rows(), map.rows());
+        assert_eq!(is_empty.cols(), map.cols());
+        assert!(is_empty[(0, 0)]);
+        assert!(!is_empty[(0, 5)]);
+        assert!(!is_empty[(3, 1)]);
+        assert!(is_empty[(5, 15)]);
+    }
+
+    #[test]
+    fn test_tile_from_char() {
+        assert_eq!(Tile::try_from('+').unwrap(), Tile::Corner);
+        assert_eq!(Tile::try_from('Q').unwrap(), Tile::Letter(b'Q'));
+        assert!(Tile::try_from('é').is_err());
+    }
+
     #[test]
     fn test_part_1() {
         let map = parse(&EXAMPLE.replace('.', " ")).unwrap();
@@ -144,4 +245,69 @@ mod tests {
         let result = part_2(&map);
         assert_eq!(result, 38);
     }
+
+    #[test]
+    fn test_walk_map_loop() {
+        let map = parse(&"|.+-+\n+-+.|\n..+-+".replace('.', " ")).unwrap();
+        let mut steps = 0;
+        let result = walk_map(&map, |_| steps += 1);
+        assert!(matches!(result, Err(WalkError::Loop)));
+        assert_eq!(steps, 12);
+    }
+
+    #[test]
+    fn test_grid_get() {
+        let mut map = parse(&EXAMPLE.replace('.', " ")).unwrap();
+        assert_eq!(map.get((0, 5)), Some(&Tile::Vertical));
+        assert_eq!(map.get((5, 15)), Some(&Tile::Empty));
+        assert_eq!(map.get((6, 0)), None);
+        assert_eq!(map.get((0, 16)), None);
+        assert_eq!(map.get_mut((0, 16)), None);
+        *map.get_mut((0, 0)).unwrap() = Tile::Corner;
+        assert_eq!(map[(0, 0)], Tile::Corner);
+    }
+
+    #[test]
+    fn test_tile_to_byte() {
+        let tiles = [Tile::Empty, Tile::Vertical, Tile::Horizontal, Tile::Corner]
+            .into_iter()
+            .chain((b'A'..=b'Z').map(Tile::Letter));
+        for tile in tiles {
+            assert_eq!(Tile::try_from(tile.to_byte()).unwrap(), tile);
+        }
+        let input = EXAMPLE.replace('.', " ");
+        let map = parse(&input).unwrap();
+        assert_eq!(map.render_with(|&tile| char::from(tile.to_byte())), input);
+    }
+
+    #[test]
+    fn test_grid_row_and_col() {
+        let map = parse(&EXAMPLE.replace('.', " ")).unwrap();
+        let row = map.row(2);
+        assert_eq!(row.len(), 16);
+        assert_eq!(row[5], Tile::Letter(b'A'));
+        assert_eq!(row[11], Tile::Letter(b'C'));
+        let col = map.col(5).copied().collect::<Vec<_>>();
+        assert_eq!(
+            col,
+            [
+                Tile::Vertical,
+                Tile::Vertical,
+                Tile::Letter(b'A'),
+                Tile::Vertical,
+                Tile::Vertical,
+                Tile::Corner
+            ]
+        );
+        assert_eq!(map.col(15).count(), 6);
+    }
+
+    #[test]
+    fn test_segment_lengths() {
+        let map = parse(&EXAMPLE.replace('.', " ")).unwrap();
+        let segments = segment_lengths(&map);
+        assert_eq!(segments[0], 5);
+        assert_eq!(segments, [5, 3, 4, 3, 4, 3, 2, 13]);
+        assert_eq!(segments.iter().sum::<usize>(), part_2(&map) - 1);
+    }
 }
@@ -1,4 +1,6 @@
+use std::cmp::Ordering;
 use std::collections::HashMap;
+use std::fmt::Display;
 use std::num::ParseIntError;
 use std::ops::{Add, Div, Mul, Sub};
 use std::str::FromStr;
@@ -13,7 +15,8 @@ enum ParseError {
     InvalidNumber(#[from] ParseIntError),
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 struct Vector {
     x: i64,
     y: i64,
@@ -24,6 +27,16 @@ impl Vector {
     const fn size(self) -> u64 {
         self.x.unsigned_abs() + self.y.unsigned_abs() + self.z.unsigned_abs()
     }
+
+    const fn components(self) -> [i64; 3] {
+        [self.x, self.y, self.z]
+    }
+}
+
+impl From<(i64, i64, i64)> for Vector {
+    fn from((x, y, z): (i64, i64, i64)) -> Self {
+        Self { x, y, z }
+    }
 }
 
 impl Add for Vector {
@@ -94,7 +107,14 @@ impl FromStr for Vector {
     }
 }
 
+impl Display for Vector {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{},{},{}", self.x, self.y, self.z)
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 struct Particle {
     position: Vector,
     velocity: Vector,
@@ -103,8 +123,50 @@ struct Particle {
 
 impl Particle {
     fn tick(&mut self) {
-        self.velocity = self.velocity + self.acceleration;
-        self.position = self.position + self.velocity;
+        *self = self.ticked();
+    }
+
+    /// The particle one tick later.
+    #[must_use]
+    fn ticked(self) -> Self {
+        let velocity = self.velocity + self.acceleration;
+        Self {
+            position: self.position + velocity,
+            velocity,
+            acceleration: self.acceleration,
+        }
+    }
+}
+
+impl Particle {
+    /// What dominates the distance in the long run, in order of importance.
+    const fn long_run_key(&self) -> (u64, u64, u64) {
+        (
+            self.acceleration.size(),
+            self.velocity.size(),
+            self.position.size(),
+        )
+    }
+}
+
+impl Ord for Particle {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.long_run_key()
+            .cmp(&other.long_run_key())
+            // Keep consistent with Eq
+            .then_with(|| {
+                (self.acceleration, self.velocity, self.position).cmp(&(
+                    other.acceleration,
+                    other.velocity,
+                    other.position,
+                ))
+            })
+    }
+}
+
+impl PartialOrd for Particle {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
     }
 }
 
@@ -127,6 +189,16 @@ impl FromStr for Particle {
     }
 }
 
+impl Display for Particle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "p=<{}>, v=<{}>, a=<{}>",
+            self.position, self.velocity, self.acceleration
+        )
+    }
+}
+
 impl Sub for Particle {
     type Output = Self;
 
@@ -145,40 +217,174 @@ fn parse(input: &str) -> Result<Vec<Particle>, ParseError> {
 
 #[aoc(day20, part1)]
 fn part_1(particles: &[Particle]) -> usize {
-    let min_acceleration = particles
-        .iter()
-        .map(|p| p.acceleration.size())
-        .min()
-        .unwrap();
-    let min_velocity = particles
-        .iter()
-        .filter(|p| p.acceleration.size() == min_acceleration)
-        .map(|p| p.velocity.size())
-        .min()
-        .unwrap();
-    particles
-        .iter()
-        .position(|p| {
-            p.acceleration.size() == min_acceleration && p.velocity.size() == min_velocity
-        })
+    particles_with_min_acceleration(particles)
+        .into_iter()
+        .min_by_key(|&i| particles[i].velocity.size())
         .unwrap()
 }
 
+/// Indices of all particles sharing the smallest acceleration, in order.
+fn particles_with_min_acceleration(particles: &[Particle]) -> Vec<usize> {
+    let Some(min_acceleration) = particles.iter().map(|p| p.acceleration.size()).min() else {
+        return Vec::new();
+    };
+    (0..particles.len())
+        .filter(|&i| particles[i].acceleration.size() == min_acceleration)
+        .collect()
+}
+
+/// Sorts the particles so the ones that stay closest in the long run come first.
+#[allow(unused)]
+fn by_acceleration(particles: &mut [Particle]) {
+    particles.sort_unstable();
+}
+
 #[aoc(day20, part2)]
 fn part_2(particles: &[Particle]) -> usize {
+    part_2_with_history(particles).0
+}
+
+/// Number of survivors, along with the number of particles remaining after each tick.
+fn part_2_with_history(particles: &[Particle]) -> (usize, Vec<usize>) {
+    simulate_with_history(particles, 99)
+}
+
+#[allow(unused)]
+fn survivors_by_simulation(particles: &[Particle], ticks: usize) -> usize {
+    simulate_with_history(particles, ticks).0
+}
+
+fn simulate_with_history(particles: &[Particle], ticks: usize) -> (usize, Vec<usize>) {
     let mut particles = particles.to_vec();
     let mut counts = HashMap::<Vector, usize>::new();
-    for _ in 1..100 {
+    let mut history = Vec::with_capacity(ticks);
+    for _ in 0..ticks {
         counts.clear();
         for particle in &mut particles {
             particle.tick();
             *counts.entry(particle.position).or_default() += 1;
         }
         particles.retain(|p| counts[&p.position] == 1);
+        history.push(particles.len());
+    }
+    (particles.len(), history)
+}
+
+/// Counts the survivors by solving for the exact time each pair of particles collide, instead of
+/// simulating for a fixed number of ticks.
+#[allow(unused)]
+fn survivors_exact(particles: &[Particle]) -> usize {
+    let mut collisions = Vec::new();
+    for (i, &p1) in particles.iter().enumerate() {
+        for (j, &p2) in particles.iter().enumerate().skip(i + 1) {
+            if let Some(time) = collision_time(p1, p2) {
+                collisions.push((time, i, j));
+            }
+        }
+    }
+    collisions.sort_unstable();
+    let mut destroyed_at = vec![None; particles.len()];
+    for (time, i, j) in collisions {
+        // Both particles must have survived until now. Several particles can collide at once.
+        if destroyed_at[i].is_none_or(|t| t == time) && destroyed_at[j].is_none_or(|t| t == time)
+        {
+            destroyed_at[i] = Some(time);
+            destroyed_at[j] = Some(time);
+        }
+    }
+    destroyed_at.into_iter().filter(Option::is_none).count()
+}
+
+/// First tick (at least 1) where the two particles are at the same position.
+fn collision_time(p1: Particle, p2: Particle) -> Option<i64> {
+    // The differences are taken in i128, since they might not fit in an i64
+    let diff = |v1: Vector, v2: Vector| {
+        v1.components()
+            .map(i128::from)
+            .into_iter()
+            .zip(v2.components().map(i128::from))
+            .map(|(c1, c2)| c1 - c2)
+    };
+    let axes = diff(p1.position, p2.position)
+        .zip(diff(p1.velocity, p2.velocity))
+        .zip(diff(p1.acceleration, p2.acceleration));
+    let mut candidates = None::<Vec<i64>>;
+    for ((p, v), a) in axes {
+        // After t ticks: p + v*t + a*t*(t+1)/2 = 0
+        let Some(roots) = positive_integer_roots(a, 2 * v + a, 2 * p) else {
+            continue; // Always zero
+        };
+        candidates = Some(match candidates {
+            None => roots,
+            Some(prev) => prev.into_iter().filter(|t| roots.contains(t)).collect(),
+        });
+    }
+    // No candidates means the particles are identical.
+    candidates.map_or(Some(1), |times| times.into_iter().min())
+}
+
+/// Integer solutions `t >= 1` of `a*t^2 + b*t + c = 0`, or `None` if every `t` is a solution.
+fn positive_integer_roots(a: i128, b: i128, c: i128) -> Option<Vec<i64>> {
+    if a == 0 && b == 0 {
+        return (c != 0).then(Vec::new);
+    }
+    let sign = |t| quadratic_sign(a, b, c, t);
+    // The polynomial is strictly monotonic on either side of its vertex at -b/2a
+    let vertex = match a.cmp(&0) {
+        Ordering::Equal => i128::from(i64::MAX),
+        Ordering::Greater => (-b).div_euclid(2 * a),
+        Ordering::Less => b.div_euclid(-2 * a),
+    };
+    let vertex = i64::try_from(vertex.clamp(0, i128::from(i64::MAX))).unwrap();
+    let mut roots = Vec::with_capacity(2);
+    roots.extend(root_between(1, vertex, sign));
+    if let Some(start) = vertex.checked_add(1) {
+        roots.extend(root_between(start, i64::MAX, sign));
+    }
+    Some(roots)
+}
+
+/// Sign of `a*t^2 + b*t + c`, evaluated without overflowing for coefficients derived from
+/// differences of `i64` values.
+fn quadratic_sign(a: i128, b: i128, c: i128, t: i64) -> Ordering {
+    let t = i128::from(t);
+    // |b| < 2^66 and |c| < 2^66, so if a*t + b, or its product with t, overflows, the sign of that
+    // term decides the sign of the whole polynomial
+    let Some(slope) = a.checked_mul(t).and_then(|at| at.checked_add(b)) else {
+        return a.cmp(&0);
+    };
+    t.checked_mul(slope).map_or_else(|| slope.cmp(&0), |product| product.cmp(&-c))
+}
+
+/// Bisects `lo..=hi` for a zero of a strictly monotonic function, given only its sign.
+fn root_between(mut lo: i64, mut hi: i64, sign: impl Fn(i64) -> Ordering) -> Option<i64> {
+    if lo > hi {
+        return None;
+    }
+    let lo_sign = sign(lo);
+    let hi_sign = sign(hi);
+    if lo_sign == Ordering::Equal {
+        return Some(lo);
+    }
+    if hi_sign == Ordering::Equal {
+        return Some(hi);
+    }
+    if lo_sign == hi_sign {
+        return None;
     }
-    particles.len()
+    while hi - lo > 1 {
+        let mid = lo + (hi - lo) / 2;
+        match sign(mid) {
+            Ordering::Equal => return Some(mid),
+            s if s == lo_sign => lo = mid,
+            _ => hi = mid,
+        }
+    }
+    None
 }
 
+solver!(parse; 1 => part_1, 2 => part_2);
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -224,6 +430,14 @@ mod tests {
         );
     }
 
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_round_trip() {
+        let particles = parse(EXAMPLE1).unwrap();
+        let json = serde_json::to_string(&particles).unwrap();
+        assert_eq!(serde_json::from_str::<Vec<Particle>>(&json).unwrap(), particles);
+    }
+
     #[test]
     fn test_part_1() {
         let particles = parse(EXAMPLE1).unwrap();
@@ -237,4 +451,157 @@ mod tests {
         let result = part_2(&particles);
         assert_eq!(result, 1);
     }
+
+    #[test]
+    fn test_part_2_with_history() {
+        let particles = parse(EXAMPLE2).unwrap();
+        let (survivors, history) = part_2_with_history(&particles);
+        assert_eq!(survivors, 1);
+        assert_eq!(history.last(), Some(&1));
+        assert!(history.is_sorted_by(|a, b| a >= b));
+        assert_eq!(&history[..3], [4, 1, 1]);
+    }
+
+    #[test]
+    fn test_survivors_exact() {
+        let particles = parse(EXAMPLE2).unwrap();
+        assert_eq!(survivors_exact(&particles), 1);
+        assert_eq!(survivors_by_simulation(&particles, 10), 1);
+    }
+
+    #[test]
+    fn test_survivors_exact_extreme() {
+        let particles = parse(&format!(
+            "p=<0,0,0>, v=<{max},0,0>, a=<{max},0,0>\n\
+             p=<0,0,0>, v=<0,0,0>, a=<0,0,0>",
+            max = i64::MAX,
+        ))
+        .unwrap();
+        assert_eq!(collision_time(particles[0], particles[1]), None);
+        assert_eq!(survivors_exact(&particles), 2);
+    }
+
+    #[test]
+    fn test_survivors_exact_huge_coefficients() {
+        // b^2 in the quadratic for the x axis does not fit in an i128
+        let particles = parse(&format!(
+            "p=<{},0,0>, v=<{},0,0>, a=<1,0,0>\n\
+             p=<0,0,0>, v=<0,0,0>, a=<0,0,0>",
+            i64::MAX - 1,
+            -i64::MAX,
+        ))
+        .unwrap();
+        assert_eq!(collision_time(particles[0], particles[1]), Some(1));
+        assert_eq!(survivors_exact(&particles), 0);
+        assert_eq!(survivors_by_simulation(&particles, 1), 0);
+    }
+
+    #[test]
+    fn test_survivors_exact_opposite_extremes() {
+        // The differences between the particles do not fit in an i64
+        let particles = parse(&format!(
+            "p=<{max},0,0>, v=<-{max},0,0>, a=<0,0,0>\n\
+             p=<-{max},0,0>, v=<{max},0,0>, a=<0,0,0>\n\
+             p=<-1,0,0>, v=<0,0,0>, a=<0,0,0>",
+            max = i64::MAX,
+        ))
+        .unwrap();
+        assert_eq!(collision_time(particles[0], particles[1]), Some(1));
+        assert_eq!(collision_time(particles[0], particles[2]), None);
+        assert_eq!(collision_time(particles[2], particles[1]), None);
+        assert_eq!(survivors_exact(&particles), 1);
+        assert_eq!(survivors_by_simulation(&particles, 1), 1);
+    }
+
+    #[test]
+    fn test_survivors_exact_mixed() {
+        let particles = parse(
+            "p=<-6,0,0>, v=<3,0,0>, a=<0,0,0>\n\
+             p=<-4,0,0>, v=<2,0,0>, a=<0,0,0>\n\
+             p=<-2,0,0>, v=<1,0,0>, a=<0,0,0>\n\
+             p=<3,0,0>, v=<-1,0,0>, a=<0,0,0>\n\
+             p=<0,5,0>, v=<0,0,0>, a=<0,-1,0>\n\
+             p=<0,2,0>, v=<0,0,0>, a=<0,0,0>\n\
+             p=<0,-1,0>, v=<0,0,0>, a=<0,0,0>\n\
+             p=<1,1,1>, v=<1,1,1>, a=<0,0,0>\n\
+             p=<5,5,5>, v=<-1,-1,-1>, a=<0,0,0>\n\
+             p=<20,0,7>, v=<-1,0,0>, a=<0,0,0>\n\
+             p=<0,0,7>, v=<1,0,0>, a=<0,0,0>\n\
+             p=<0,0,-3>, v=<1,0,0>, a=<0,0,0>\n\
+             p=<1,0,-3>, v=<-1,0,0>, a=<0,0,0>",
+        )
+        .unwrap();
+        // Three particles collide at once, one would have been hit by a particle that is already
+        // destroyed, and two pass each other between ticks.
+        assert_eq!(survivors_exact(&particles), 4);
+        assert_eq!(survivors_by_simulation(&particles, 20), 4);
+    }
+
+    #[test]
+    fn test_vector_components() {
+        let vector = Vector::from((3, -1, 7));
+        assert_eq!(vector, Vector { x: 3, y: -1, z: 7 });
+        assert_eq!(vector.components(), [3, -1, 7]);
+        let tuple = (-4, 0, 12);
+        let [x, y, z] = Vector::from(tuple).components();
+        assert_eq!(x, tuple.0);
+        assert_eq!(y, tuple.1);
+        assert_eq!(z, tuple.2);
+    }
+
+    #[test]
+    fn test_by_acceleration() {
+        let particles = parse(EXAMPLE1).unwrap();
+        let mut sorted = particles.clone();
+        by_acceleration(&mut sorted);
+        assert_eq!(sorted[0].acceleration.size(), 1);
+        assert!(sorted[0].acceleration.size() < sorted[1].acceleration.size());
+        assert_eq!(sorted[0], particles[part_1(&particles)]);
+    }
+
+    #[test]
+    fn test_particles_with_min_acceleration() {
+        let particles = parse(
+            "p=<0,0,0>, v=<5,0,0>, a=<1,0,0>\n\
+             p=<0,0,0>, v=<0,0,0>, a=<0,2,0>\n\
+             p=<0,0,0>, v=<0,-2,0>, a=<0,0,-1>\n\
+             p=<0,0,0>, v=<0,0,0>, a=<1,1,1>\n\
+             p=<9,0,0>, v=<1,1,0>, a=<-1,0,0>",
+        )
+        .unwrap();
+        assert_eq!(particles_with_min_acceleration(&particles), [0, 2, 4]);
+        assert_eq!(part_1(&particles), 2);
+        assert_eq!(particles_with_min_acceleration(&[]), Vec::<usize>::new());
+    }
+
+    #[test]
+    fn test_display() {
+        let line = "p=<3,-1,0>, v=<2,0,-7>, a=<-1,0,10>";
+        let particles = parse(line).unwrap();
+        assert_eq!(particles[0].to_string(), line);
+        assert_eq!(particles[0].position.to_string(), "3,-1,0");
+
+        // Padding in the input is not preserved, but the values are
+        let particles = parse(EXAMPLE2).unwrap();
+        let emitted = particles
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join("\n");
+        assert_eq!(emitted.lines().next(), Some("p=<-6,0,0>, v=<3,0,0>, a=<0,0,0>"));
+        assert_eq!(parse(&emitted).unwrap(), particles);
+    }
+
+    #[test]
+    fn test_ticked() {
+        for p in parse(EXAMPLE1).unwrap() {
+            let mut expected = p;
+            expected.tick();
+            assert_eq!(p.ticked(), expected);
+            expected.tick();
+            assert_eq!(p.ticked().ticked(), expected);
+        }
+        let p: Particle = "p=<3,0,0>, v=<2,0,0>, a=<-1,0,0>".parse().unwrap();
+        assert_eq!(p.ticked().to_string(), "p=<4,0,0>, v=<1,0,0>, a=<-1,0,0>");
+    }
 }
@@ -9,6 +9,14 @@ enum ParseError {
     SyntaxError,
 }
 
+#[derive(Debug, Error, PartialEq, Eq)]
+enum MissingRule {
+    #[error("No rule matches the 2x2 pattern {0:04b}")]
+    Small(u8),
+    #[error("No rule matches the 3x3 pattern {0:09b}")]
+    Large(u16),
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 struct SmallRule {
     pattern: u8,      // 4 bits
@@ -38,6 +46,16 @@ impl SmallRule {
         }
         res
     }
+
+    /// The different patterns among the [`SmallRule::variations`], sorted. Symmetric patterns
+    /// have fewer than 8.
+    #[allow(unused)]
+    fn distinct_variations(self) -> Vec<u8> {
+        let mut patterns = self.variations().map(|rule| rule.pattern).to_vec();
+        patterns.sort_unstable();
+        patterns.dedup();
+        patterns
+    }
 }
 
 impl FromStr for SmallRule {
@@ -104,6 +122,16 @@ impl LargeRule {
         }
         res
     }
+
+    /// The different patterns among the [`LargeRule::variations`], sorted. Symmetric patterns
+    /// have fewer than 8.
+    #[allow(unused)]
+    fn distinct_variations(self) -> Vec<u16> {
+        let mut patterns = self.variations().map(|rule| rule.pattern).to_vec();
+        patterns.sort_unstable();
+        patterns.dedup();
+        patterns
+    }
 }
 
 impl FromStr for LargeRule {
@@ -161,14 +189,22 @@ fn parse(input: &str) -> Result<Vec<Rule>, ParseError> {
 
 #[aoc(day21, part1)]
 fn part_1(rules: &[Rule]) -> u32 {
+    *generation_bit_counts(rules, 5).last().unwrap()
+}
+
+/// Number of pixels that are on after each generation, starting with generation 0.
+fn generation_bit_counts(rules: &[Rule], iterations: u32) -> Vec<u32> {
     let (small, large) = create_lookups(rules);
     let mut pattern: Vec<u64> = vec![0b010, 0b001, 0b111];
     let mut next = Vec::new();
-    for _ in 1..=5 {
+    let mut counts = Vec::with_capacity(iterations as usize + 1);
+    counts.push(pattern.iter().copied().map(u64::count_ones).sum());
+    for _ in 1..=iterations {
         expand_grid(&pattern, &mut next, &small, &large);
         (pattern, next) = (next, pattern);
+        counts.push(pattern.iter().copied().map(u64::count_ones).sum());
     }
-    pattern.into_iter().map(u64::count_ones).sum()
+    counts
 }
 
 fn create_lookups(rules: &[Rule]) -> ([u16; 16], [u16; 512]) {
@@ -191,6 +227,34 @@ fn create_lookups(rules: &[Rule]) -> ([u16; 16], [u16; 512]) {
     (small, large)
 }
 
+/// Like [`create_lookups`], but fails if any pattern is not matched by a rule.
+#[allow(unused)]
+fn create_lookups_checked(rules: &[Rule]) -> Result<([u16; 16], [u16; 512]), MissingRule> {
+    let mut small_covered = [false; 16];
+    let mut large_covered = [false; 512];
+    for pat in rules {
+        match pat {
+            Rule::Small(small_rule) => {
+                for var in small_rule.variations() {
+                    small_covered[var.pattern as usize] = true;
+                }
+            }
+            Rule::Large(large_rule) => {
+                for var in large_rule.variations() {
+                    large_covered[var.pattern as usize] = true;
+                }
+            }
+        }
+    }
+    if let Some(pattern) = (0..).zip(small_covered).find_map(|(p, c)| (!c).then_some(p)) {
+        return Err(MissingRule::Small(pattern));
+    }
+    if let Some(pattern) = (0..).zip(large_covered).find_map(|(p, c)| (!c).then_some(p)) {
+        return Err(MissingRule::Large(pattern));
+    }
+    Ok(create_lookups(rules))
+}
+
 fn expand_grid(pattern: &[u64], next: &mut Vec<u64>, small: &[u16; 16], large: &[u16; 512]) {
     next.clear();
     let n = pattern.len();
@@ -316,9 +380,12 @@ fn count_expanded(pattern: u16, small: &[u16; 16], large: &[u16; 512]) -> Vec<(u
     counts
 }
 
+solver!(parse; 1 => part_1, 2 => part_2);
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use test_case::test_case;
 
     const EXAMPLE: &str = "\
     ../.# => ##./#../...\n\
@@ -353,4 +420,52 @@ mod tests {
             print_grid(&pattern);
         }
     }
+
+    #[test]
+    fn test_generation_bit_counts() {
+        let rules = parse(EXAMPLE).unwrap();
+        let counts = generation_bit_counts(&rules, 2);
+        assert_eq!(counts, [5, 4, 12]);
+    }
+
+    #[test]
+    fn test_create_lookups_checked() {
+        let rules = parse(EXAMPLE).unwrap();
+        assert_eq!(create_lookups_checked(&rules), Err(MissingRule::Small(0)));
+        let mut rules = (0..16)
+            .map(|pattern| Rule::Small(SmallRule { pattern, replacement: 0 }))
+            .chain((0..512).map(|pattern| Rule::Large(LargeRule { pattern, replacement: 0 })))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            create_lookups_checked(&rules),
+            Ok(create_lookups(&rules))
+        );
+        // All on has no other variations
+        rules.pop();
+        assert_eq!(
+            create_lookups_checked(&rules),
+            Err(MissingRule::Large(0b111_111_111))
+        );
+    }
+
+    #[test_case("##/## => .../.../..." => 1; "small all on")]
+    #[test_case("../.. => .../.../..." => 1; "small all off")]
+    #[test_case("#./.. => .../.../..." => 4; "small corner")]
+    #[test_case("#./.# => .../.../..." => 2; "small diagonal")]
+    fn test_small_distinct_variations(rule: &str) -> usize {
+        let rule: SmallRule = rule.parse().unwrap();
+        let distinct = rule.distinct_variations();
+        assert!(distinct.contains(&rule.pattern));
+        distinct.len()
+    }
+
+    #[test_case("#.#/.#./#.# => ..../..../..../...." => 1; "large cross")]
+    #[test_case("#../.../... => ..../..../..../...." => 4; "large corner")]
+    #[test_case(".#./..#/### => ..../..../..../...." => 8; "large glider")]
+    fn test_large_distinct_variations(rule: &str) -> usize {
+        let rule: LargeRule = rule.parse().unwrap();
+        let distinct = rule.distinct_variations();
+        assert!(distinct.contains(&rule.pattern));
+        distinct.len()
+    }
 }
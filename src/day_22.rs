@@ -1,18 +1,23 @@
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap};
 use std::ops::{Index, IndexMut};
 use std::str::FromStr;
 
 use thiserror::Error;
 
+use crate::utils::Grid;
+
 #[derive(Debug, Error)]
 enum ParseError {
     #[error("Cell invalid state")]
     InvalidState,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(into = "char", try_from = "char"))]
 #[repr(u8)]
 enum State {
+    #[default]
     Clean = b'.',
     Weakened = b'W',
     Infected = b'#',
@@ -25,18 +30,171 @@ impl TryFrom<u8> for State {
     fn try_from(value: u8) -> Result<Self, Self::Error> {
         Ok(match value {
             b'.' => Self::Clean,
+            b'W' => Self::Weakened,
             b'#' => Self::Infected,
+            b'F' => Self::Flagged,
             _ => return Err(ParseError::InvalidState),
         })
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+impl From<State> for char {
+    fn from(value: State) -> Self {
+        value as u8 as Self
+    }
+}
+
+impl TryFrom<char> for State {
+    type Error = ParseError;
+
+    fn try_from(value: char) -> Result<Self, Self::Error> {
+        if value.is_ascii() {
+            Self::try_from(value as u8)
+        } else {
+            Err(ParseError::InvalidState)
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Cells {
+    Sparse(HashMap<(i32, i32), State>),
+    Dense {
+        grid: Grid<State>,
+        origin: (i32, i32), // position of grid[(0, 0)]
+    },
+}
+
+#[derive(Debug, Clone)]
 struct Map {
-    cells: HashMap<(i32, i32), State>,
+    cells: Cells,
     fallback: State, // default for Index trait
 }
 
+impl Map {
+    /// Copy of the map backed by a dense grid, that grows as the carrier nears the edges.
+    fn dense(&self) -> Self {
+        let stored = self.stored_cells();
+        let min_r = stored.iter().map(|&((r, _), _)| r).min().unwrap_or(0);
+        let max_r = stored.iter().map(|&((r, _), _)| r).max().unwrap_or(0);
+        let min_c = stored.iter().map(|&((_, c), _)| c).min().unwrap_or(0);
+        let max_c = stored.iter().map(|&((_, c), _)| c).max().unwrap_or(0);
+        let rows = usize::try_from(max_r - min_r + 1).unwrap();
+        let cols = usize::try_from(max_c - min_c + 1).unwrap();
+        let mut map = Self {
+            cells: Cells::Dense {
+                grid: filled_grid(rows, cols, self.fallback),
+                origin: (min_r, min_c),
+            },
+            fallback: self.fallback,
+        };
+        for (pos, state) in stored {
+            map[pos] = state;
+        }
+        map
+    }
+
+    /// The cells from `min` to `max`, inclusive, one row per line, in the format read by parsing.
+    ///
+    /// Parsing puts the middle of the text at `(0, 0)`, so the positions survive a round trip when
+    /// the bounds are centered there, like `((-n, -n), (n, n))`.
+    #[allow(unused)]
+    fn to_string(&self, (min, max): ((i32, i32), (i32, i32))) -> String {
+        (min.0..=max.0)
+            .map(|r| {
+                (min.1..=max.1)
+                    .map(|c| char::from(self[(r, c)]))
+                    .collect::<String>()
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// All cells that have an explicit value, in no particular order.
+    fn stored_cells(&self) -> Vec<((i32, i32), State)> {
+        match &self.cells {
+            Cells::Sparse(cells) => cells.iter().map(|(&pos, &state)| (pos, state)).collect(),
+            Cells::Dense { grid, origin } => {
+                let mut cells = Vec::with_capacity(grid.rows() * grid.cols());
+                for (r, row) in (0..grid.rows()).zip(origin.0..) {
+                    for (c, col) in (0..grid.cols()).zip(origin.1..) {
+                        cells.push(((row, col), grid[(r, c)]));
+                    }
+                }
+                cells
+            }
+        }
+    }
+}
+
+fn filled_grid(rows: usize, cols: usize, state: State) -> Grid<State> {
+    let mut grid = Grid::new(rows, cols);
+    for r in 0..rows {
+        for c in 0..cols {
+            grid[(r, c)] = state;
+        }
+    }
+    grid
+}
+
+/// Grows a dense grid until it contains `pos`, roughly doubling in size each time, with the old
+/// contents kept in the center.
+fn grow_to_contain(grid: &mut Grid<State>, origin: &mut (i32, i32), pos: (i32, i32), fill: State) {
+    let mut rows = i32::try_from(grid.rows()).unwrap();
+    let mut cols = i32::try_from(grid.cols()).unwrap();
+    let mut new_origin = *origin;
+    while pos.0 < new_origin.0 || pos.0 >= new_origin.0 + rows {
+        let pad = (rows + 1) / 2;
+        new_origin.0 -= pad;
+        rows += 2 * pad;
+    }
+    while pos.1 < new_origin.1 || pos.1 >= new_origin.1 + cols {
+        let pad = (cols + 1) / 2;
+        new_origin.1 -= pad;
+        cols += 2 * pad;
+    }
+    if new_origin == *origin {
+        return;
+    }
+    let mut new_grid = filled_grid(
+        usize::try_from(rows).unwrap(),
+        usize::try_from(cols).unwrap(),
+        fill,
+    );
+    let offset_r = usize::try_from(origin.0 - new_origin.0).unwrap();
+    let offset_c = usize::try_from(origin.1 - new_origin.1).unwrap();
+    for r in 0..grid.rows() {
+        for c in 0..grid.cols() {
+            new_grid[(r + offset_r, c + offset_c)] = grid[(r, c)];
+        }
+    }
+    *grid = new_grid;
+    *origin = new_origin;
+}
+
+fn dense_index(grid: &Grid<State>, origin: (i32, i32), pos: (i32, i32)) -> Option<(usize, usize)> {
+    let r = usize::try_from(pos.0 - origin.0).ok()?;
+    let c = usize::try_from(pos.1 - origin.1).ok()?;
+    (r < grid.rows() && c < grid.cols()).then_some((r, c))
+}
+
+impl PartialEq for Map {
+    fn eq(&self, other: &Self) -> bool {
+        // Compare contents, regardless of how the cells are stored
+        self.fallback == other.fallback
+            && self
+                .stored_cells()
+                .into_iter()
+                .all(|(pos, state)| other[pos] == state)
+            && other
+                .stored_cells()
+                .into_iter()
+                .all(|(pos, state)| self[pos] == state)
+    }
+}
+
+impl Eq for Map {}
+
 impl FromStr for Map {
     type Err = ParseError;
 
@@ -52,7 +210,7 @@ impl FromStr for Map {
             }
         }
         Ok(Self {
-            cells,
+            cells: Cells::Sparse(cells),
             fallback: State::Clean,
         })
     }
@@ -62,13 +220,24 @@ impl Index<(i32, i32)> for Map {
     type Output = State;
 
     fn index(&self, index: (i32, i32)) -> &Self::Output {
-        self.cells.get(&index).unwrap_or(&self.fallback)
+        match &self.cells {
+            Cells::Sparse(cells) => cells.get(&index).unwrap_or(&self.fallback),
+            Cells::Dense { grid, origin } => dense_index(grid, *origin, index)
+                .map_or(&self.fallback, |grid_index| &grid[grid_index]),
+        }
     }
 }
 
 impl IndexMut<(i32, i32)> for Map {
     fn index_mut(&mut self, index: (i32, i32)) -> &mut Self::Output {
-        self.cells.entry(index).or_insert(self.fallback)
+        match &mut self.cells {
+            Cells::Sparse(cells) => cells.entry(index).or_insert(self.fallback),
+            Cells::Dense { grid, origin } => {
+                grow_to_contain(grid, origin, index, self.fallback);
+                let grid_index = dense_index(grid, *origin, index).unwrap();
+                &mut grid[grid_index]
+            }
+        }
     }
 }
 
@@ -117,6 +286,37 @@ impl Direction {
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Turn {
+    Left,
+    Right,
+}
+
+impl Turn {
+    const fn apply(self, direction: Direction) -> Direction {
+        match self {
+            Self::Left => direction.turn_left(),
+            Self::Right => direction.turn_right(),
+        }
+    }
+}
+
+/// How the carrier turns in basic mode, depending on the current cell.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct TurnRules {
+    clean: Turn,
+    infected: Turn,
+}
+
+impl Default for TurnRules {
+    fn default() -> Self {
+        Self {
+            clean: Turn::Left,
+            infected: Turn::Right,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 struct Infection {
     position: (i32, i32),
@@ -128,17 +328,36 @@ impl Infection {
     fn new() -> Self {
         Self::default()
     }
+
+    /// Resumes a carrier on a saved map. The map is centered the same way as when parsing.
+    #[allow(unused)]
+    fn from_lines(
+        map_str: &str,
+        position: (i32, i32),
+        direction: Direction,
+        count_infected: usize,
+    ) -> Result<(Self, Map), ParseError> {
+        let infection = Self {
+            position,
+            direction,
+            count_infected,
+        };
+        Ok((infection, map_str.parse()?))
+    }
     fn basic_step(&mut self, map: &mut Map) {
+        self.basic_step_with(map, TurnRules::default());
+    }
+    fn basic_step_with(&mut self, map: &mut Map, rules: TurnRules) {
         match map[self.position] {
             State::Clean => {
                 map[self.position] = State::Infected;
-                self.direction = self.direction.turn_left();
+                self.direction = rules.clean.apply(self.direction);
                 self.position = self.direction.step(self.position);
                 self.count_infected += 1;
             }
             State::Infected => {
                 map[self.position] = State::Clean;
-                self.direction = self.direction.turn_right();
+                self.direction = rules.infected.apply(self.direction);
                 self.position = self.direction.step(self.position);
             }
             State::Weakened | State::Flagged => unimplemented!(),
@@ -170,6 +389,23 @@ impl Infection {
     }
 }
 
+/// Cells whose state differs between the two maps, as `(position, before, after)`, sorted by
+/// position.
+#[allow(unused)]
+fn changed_cells(before: &Map, after: &Map) -> Vec<((i32, i32), State, State)> {
+    let positions: BTreeSet<_> = before
+        .stored_cells()
+        .into_iter()
+        .chain(after.stored_cells())
+        .map(|(pos, _)| pos)
+        .collect();
+    positions
+        .into_iter()
+        .filter(|&pos| before[pos] != after[pos])
+        .map(|pos| (pos, before[pos], after[pos]))
+        .collect()
+}
+
 #[aoc_generator(day22)]
 fn parse(input: &str) -> Result<Map, ParseError> {
     input.parse()
@@ -187,7 +423,7 @@ fn part_1(map: &Map) -> usize {
 
 #[aoc(day22, part2)]
 fn part_2(map: &Map) -> usize {
-    let mut map = map.clone();
+    let mut map = map.dense();
     let mut infection = Infection::new();
     for _ in 0..10_000_000 {
         infection.evolved_step(&mut map);
@@ -195,6 +431,8 @@ fn part_2(map: &Map) -> usize {
     infection.count_infected
 }
 
+solver!(parse; 1 => part_1, 2 => part_2);
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -214,6 +452,15 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_state_from_char() {
+        assert_eq!(State::try_from('#').unwrap(), State::Infected);
+        assert_eq!(State::try_from('.').unwrap(), State::Clean);
+        assert_eq!(State::try_from('W').unwrap(), State::Weakened);
+        assert_eq!(State::try_from('F').unwrap(), State::Flagged);
+        assert!(State::try_from('█').is_err());
+    }
+
     #[test]
     fn test_part_1() {
         let map = parse(EXAMPLE).unwrap();
@@ -221,10 +468,120 @@ mod tests {
         assert_eq!(result, 5_587);
     }
 
+    #[test]
+    fn test_swapped_turn_rules() {
+        let rules = TurnRules {
+            clean: Turn::Right,
+            infected: Turn::Left,
+        };
+        let run = |map: &mut Map, rules| {
+            let mut infection = Infection::new();
+            for _ in 0..10_000 {
+                infection.basic_step_with(map, rules);
+            }
+            infection.count_infected
+        };
+        let swapped = run(&mut parse(EXAMPLE).unwrap(), rules);
+        assert_ne!(swapped, 5_587);
+        // Swapping left and right is the same as running on a mirrored map
+        let mirrored = run(&mut parse("#..\n..#\n...").unwrap(), TurnRules::default());
+        assert_eq!(swapped, mirrored);
+    }
+
+    #[test]
+    fn test_dense_map() {
+        let mut sparse = parse(EXAMPLE).unwrap();
+        let mut dense = sparse.dense();
+        assert_eq!(sparse, dense);
+        let mut sparse_infection = Infection::new();
+        let mut dense_infection = Infection::new();
+        for _ in 0..100_000 {
+            sparse_infection.evolved_step(&mut sparse);
+            dense_infection.evolved_step(&mut dense);
+        }
+        assert_eq!(sparse_infection, dense_infection);
+        assert_eq!(sparse, dense);
+        assert_ne!(dense, parse(EXAMPLE).unwrap());
+    }
+
     #[test]
     fn test_part_2() {
         let map = parse(EXAMPLE).unwrap();
         let result = part_2(&map);
         assert_eq!(result, 2_511_944);
     }
+
+    #[test]
+    fn test_changed_cells() {
+        let before = parse(EXAMPLE).unwrap();
+        let mut after = before.clone();
+        assert_eq!(changed_cells(&before, &after), []);
+        let mut infection = Infection::new();
+        infection.evolved_step(&mut after);
+        assert_eq!(
+            changed_cells(&before, &after),
+            [((0, 0), State::Clean, State::Weakened)]
+        );
+        let dense = after.dense();
+        infection.evolved_step(&mut after);
+        assert_eq!(
+            changed_cells(&dense, &after),
+            [((0, -1), State::Infected, State::Flagged)]
+        );
+    }
+
+    #[test]
+    fn test_infection_from_lines() {
+        let mut map = parse(EXAMPLE).unwrap();
+        let mut infection = Infection::new();
+        for _ in 0..20 {
+            infection.evolved_step(&mut map);
+        }
+        let saved = (-4..=4)
+            .map(|r| (-4..=4).map(|c| char::from(map[(r, c)])).collect::<String>())
+            .collect::<Vec<_>>()
+            .join("\n");
+        assert!(saved.contains('W') && saved.contains('F'));
+        let (mut resumed, mut resumed_map) = Infection::from_lines(
+            &saved,
+            infection.position,
+            infection.direction,
+            infection.count_infected,
+        )
+        .unwrap();
+        assert_eq!(resumed, infection);
+        assert_eq!(resumed_map, map);
+        infection.evolved_step(&mut map);
+        resumed.evolved_step(&mut resumed_map);
+        assert_eq!(resumed, infection);
+        assert_eq!(resumed_map, map);
+    }
+
+    #[test]
+    fn test_map_to_string() {
+        let map = parse(EXAMPLE).unwrap();
+        assert_eq!(map.to_string(((-1, -1), (1, 1))), EXAMPLE);
+
+        for dense in [false, true] {
+            let mut map = parse(EXAMPLE).unwrap();
+            if dense {
+                map = map.dense();
+            }
+            let mut infection = Infection::new();
+            for _ in 0..100 {
+                infection.evolved_step(&mut map);
+            }
+            let n = map
+                .stored_cells()
+                .into_iter()
+                .filter(|&(_, state)| state != State::Clean)
+                .map(|((r, c), _)| r.abs().max(c.abs()))
+                .max()
+                .unwrap();
+            let saved = map.to_string(((-n, -n), (n, n)));
+            assert!(["W", "#", "F"].iter().all(|state| saved.contains(state)));
+            assert_eq!(parse(&saved).unwrap(), map);
+            assert_eq!(parse(&saved).unwrap().to_string(((-n, -n), (n, n))), saved);
+        }
+    }
 }
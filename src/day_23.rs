@@ -7,6 +7,8 @@ use std::{
 
 use thiserror::Error;
 
+use crate::common;
+
 #[derive(Debug, Error)]
 enum ParseError {
     #[error("Syntax error")]
@@ -57,6 +59,10 @@ impl Reg {
             _ => return Err(ParseError::InvalidRegister),
         })
     }
+
+    const fn letter(self) -> u8 {
+        self as u8 + b'a'
+    }
 }
 
 impl FromStr for Reg {
@@ -115,6 +121,7 @@ impl Display for RegOrValue {
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum BinOp {
     Set,
+    Add,
     Sub,
     Mul,
     Mod,
@@ -124,6 +131,7 @@ impl BinOp {
     const fn apply(self, target: &mut i64, rhs: i64) {
         match self {
             Self::Set => *target = rhs,
+            Self::Add => *target = target.checked_add(rhs).expect("overflow"),
             Self::Sub => *target = target.checked_sub(rhs).expect("overflow"),
             Self::Mul => *target = target.checked_mul(rhs).expect("overflow"),
             Self::Mod => *target = target.checked_rem(rhs).expect("overflow"),
@@ -137,6 +145,7 @@ impl FromStr for BinOp {
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         Ok(match s {
             "set" => Self::Set,
+            "add" => Self::Add,
             "sub" => Self::Sub,
             "mul" => Self::Mul,
             "mod" => Self::Mod,
@@ -149,6 +158,7 @@ impl Display for BinOp {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Self::Set => f.write_str("set"),
+            Self::Add => f.write_str("add"),
             Self::Sub => f.write_str("sub"),
             Self::Mul => f.write_str("mul"),
             Self::Mod => f.write_str("mod"),
@@ -172,7 +182,7 @@ impl FromStr for Instruction {
                 words.next().ok_or(ParseError::SyntaxError)?.parse()?,
                 words.next().ok_or(ParseError::SyntaxError)?.parse()?,
             ),
-            op @ ("set" | "sub" | "mul" | "mod") => Self::BinOp(
+            op @ ("set" | "add" | "sub" | "mul" | "mod") => Self::BinOp(
                 op.parse()?,
                 words.next().ok_or(ParseError::SyntaxError)?.parse()?,
                 words.next().ok_or(ParseError::SyntaxError)?.parse()?,
@@ -182,6 +192,62 @@ impl FromStr for Instruction {
     }
 }
 
+#[allow(unused)]
+impl Instruction {
+    /// Registers whose values are used by the instruction
+    fn registers_read(&self) -> Vec<Reg> {
+        let mut regs = Vec::with_capacity(2);
+        match *self {
+            Self::BinOp(op, reg, value) => {
+                if op != BinOp::Set {
+                    regs.push(reg);
+                }
+                if let RegOrValue::Reg(reg) = value {
+                    regs.push(reg);
+                }
+            }
+            Self::Jnz(cond, delta) => {
+                for value in [cond, delta] {
+                    if let RegOrValue::Reg(reg) = value {
+                        regs.push(reg);
+                    }
+                }
+            }
+        }
+        regs
+    }
+
+    /// Absolute target of a jump with a literal offset, if the instruction is at `ip`.
+    /// Returns `None` for other instructions, or if the target would be before the start.
+    fn jump_target(&self, ip: usize) -> Option<usize> {
+        match *self {
+            Self::Jnz(_, RegOrValue::Value(delta)) => {
+                ip.checked_add_signed(isize::try_from(delta).ok()?)
+            }
+            _ => None,
+        }
+    }
+
+    /// Rewrites `add x k` with a literal `k` into the equivalent `sub x -k`, so both forms
+    /// compare equal. Other instructions are returned unchanged.
+    const fn normalized(self) -> Self {
+        match self {
+            Self::BinOp(BinOp::Add, reg, RegOrValue::Value(v)) if v != i64::MIN => {
+                Self::BinOp(BinOp::Sub, reg, RegOrValue::Value(-v))
+            }
+            _ => self,
+        }
+    }
+
+    /// Register that is modified by the instruction, if any
+    const fn registers_written(&self) -> Option<Reg> {
+        match *self {
+            Self::BinOp(_, reg, _) => Some(reg),
+            Self::Jnz(..) => None,
+        }
+    }
+}
+
 impl Display for Instruction {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -191,6 +257,31 @@ impl Display for Instruction {
     }
 }
 
+/// The same instruction in the instruction set shared with day 18.
+#[allow(unused)]
+fn to_common(instr: Instruction) -> common::Instruction {
+    let reg = |reg: Reg| common::Reg::new(reg.letter()).unwrap();
+    let value = |value| match value {
+        RegOrValue::Reg(r) => common::RegOrValue::Reg(reg(r)),
+        RegOrValue::Value(v) => common::RegOrValue::Value(v),
+    };
+    match instr.normalized() {
+        Instruction::BinOp(op, r, rhs) => {
+            let op = match op {
+                BinOp::Set => common::BinOp::Set,
+                BinOp::Add => common::BinOp::Add,
+                BinOp::Sub => common::BinOp::Sub,
+                BinOp::Mul => common::BinOp::Mul,
+                BinOp::Mod => common::BinOp::Mod,
+            };
+            common::Instruction::BinOp(op, reg(r), value(rhs))
+        }
+        Instruction::Jnz(check, delta) => {
+            common::Instruction::Jump(common::Condition::NonZero, value(check), value(delta))
+        }
+    }
+}
+
 #[aoc_generator(day23)]
 fn parse(input: &str) -> Result<Vec<Instruction>, ParseError> {
     input.lines().map(str::parse).collect()
@@ -212,6 +303,16 @@ fn part_2(instructions: &[Instruction]) -> i64 {
     machine[Reg::H]
 }
 
+#[allow(unused)]
+fn solve_part1_str(input: &str) -> Result<usize, ParseError> {
+    Ok(part_1(&parse(input)?))
+}
+
+#[allow(unused)]
+fn solve_part2_str(input: &str) -> Result<i64, ParseError> {
+    Ok(part_2(&parse(input)?))
+}
+
 fn optimize(instructions: &[Instruction]) -> Vec<Instruction> {
     let target = [
         Instruction::BinOp(BinOp::Set, Reg::E, RegOrValue::Value(2)),
@@ -272,6 +373,7 @@ fn optimize(instructions: &[Instruction]) -> Vec<Instruction> {
             .iter()
             .zip(i + target.len()..)
         {
+            // Jumps out through the start still cross the replaced block
             if let Instruction::Jnz(_, RegOrValue::Value(ref mut v)) = before
                 && j.saturating_add_signed(isize::try_from(*v).unwrap()) < i
             {
@@ -289,9 +391,8 @@ fn optimize(instructions: &[Instruction]) -> Vec<Instruction> {
 fn print_program(instructions: &[Instruction]) {
     let mut targets = vec![false; instructions.len()];
     for (i, instr) in instructions.iter().enumerate() {
-        if let &Instruction::Jnz(_, RegOrValue::Value(v)) = instr
-            && let Some(j) = i.checked_add_signed(isize::try_from(v).unwrap())
-            && (0..instructions.len()).contains(&j)
+        if let Some(j) = instr.jump_target(i)
+            && j < instructions.len()
         {
             targets[j] = true;
         }
@@ -311,6 +412,7 @@ fn extract_register_mapping(
 ) -> Option<RegisterMapping> {
     let mut mapping = RegisterMapping::new();
     for (&ins1, &ins2) in instructions.iter().zip(target) {
+        let ins1 = ins1.normalized();
         match (ins1, ins2) {
             (Instruction::BinOp(op1, reg1, _), Instruction::BinOp(op2, reg2, _)) if op1 == op2 => {
                 if !mapping.try_insert(reg1, reg2) {
@@ -366,6 +468,25 @@ fn extract_register_mapping(
     Some(mapping)
 }
 
+/// Applies the renaming from the registers on the left side of `mapping` to the ones on the right.
+#[allow(unused)]
+fn rename_registers(program: &[Instruction], mapping: &RegisterMapping) -> Vec<Instruction> {
+    program
+        .iter()
+        .map(|&instr| match instr {
+            Instruction::BinOp(op, reg, val) => Instruction::BinOp(
+                op,
+                mapping.rename_reg(reg),
+                mapping.rename_reg_or_value(val),
+            ),
+            Instruction::Jnz(cond, delta) => Instruction::Jnz(
+                mapping.rename_reg_or_value(cond),
+                mapping.rename_reg_or_value(delta),
+            ),
+        })
+        .collect()
+}
+
 #[derive(Debug, Clone, Default)]
 struct RegisterMapping {
     forward: [Option<Reg>; Reg::all().len()],
@@ -387,6 +508,16 @@ impl RegisterMapping {
             _ => false,
         }
     }
+    /// Where `reg` is renamed to, leaving unmapped registers as they are.
+    fn rename_reg(&self, reg: Reg) -> Reg {
+        self.forward[reg as usize].unwrap_or(reg)
+    }
+    fn rename_reg_or_value(&self, val: RegOrValue) -> RegOrValue {
+        match val {
+            RegOrValue::Value(_) => val,
+            RegOrValue::Reg(reg) => RegOrValue::Reg(self.rename_reg(reg)),
+        }
+    }
     const fn reverse_reg(&self, reg: Reg) -> Option<Reg> {
         self.reverse[reg as usize]
     }
@@ -472,6 +603,16 @@ impl<'a> Machine<'a> {
             self.step();
         }
     }
+
+    #[allow(unused)]
+    const fn state(&self) -> State {
+        self.state
+    }
+
+    #[allow(unused)]
+    fn has_halted(&self) -> bool {
+        self.state == State::Stopped
+    }
 }
 
 impl Index<Reg> for Machine<'_> {
@@ -487,3 +628,165 @@ impl IndexMut<Reg> for Machine<'_> {
         &mut self.registers[reg as usize]
     }
 }
+
+solver!(parse; 1 => part_1, 2 => part_2);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+        set b 3\n\
+        set c 2\n\
+        mul c c\n\
+        sub b 1\n\
+        jnz b -2\
+        ";
+
+    #[test]
+    fn test_solve_part1_str() {
+        assert_eq!(solve_part1_str(EXAMPLE).unwrap(), 3);
+        assert!(solve_part1_str("snd a").is_err());
+    }
+
+    #[test]
+    fn test_machine_state() {
+        let instructions = parse(EXAMPLE).unwrap();
+        let mut machine = Machine::new(&instructions);
+        assert_eq!(machine.state(), State::Pending);
+        assert!(!machine.has_halted());
+        machine.run();
+        assert_eq!(machine.state(), State::Stopped);
+        assert!(machine.has_halted());
+    }
+
+    #[test]
+    fn test_registers_read_and_written() {
+        let sub: Instruction = "sub a b".parse().unwrap();
+        assert_eq!(sub.registers_read(), [Reg::A, Reg::B]);
+        assert_eq!(sub.registers_written(), Some(Reg::A));
+
+        let set: Instruction = "set c 5".parse().unwrap();
+        assert_eq!(set.registers_read(), []);
+        assert_eq!(set.registers_written(), Some(Reg::C));
+
+        let jnz: Instruction = "jnz a 2".parse().unwrap();
+        assert_eq!(jnz.registers_read(), [Reg::A]);
+        assert_eq!(jnz.registers_written(), None);
+    }
+
+    #[test]
+    fn test_jump_target() {
+        let jnz: Instruction = "jnz a -8".parse().unwrap();
+        assert_eq!(jnz.jump_target(10), Some(2));
+        assert_eq!(jnz.jump_target(7), None);
+        let jnz: Instruction = "jnz 1 a".parse().unwrap();
+        assert_eq!(jnz.jump_target(10), None);
+        let set: Instruction = "set a 8".parse().unwrap();
+        assert_eq!(set.jump_target(10), None);
+    }
+
+    #[test]
+    fn test_add_and_sub() {
+        let instructions = parse("set a 5\nadd a 3\nsub a -2\nadd b a\nsub b 4\nmul a b").unwrap();
+        assert_eq!(instructions[1].to_string(), "add a 3");
+        assert_eq!(instructions[1].normalized(), "sub a -3".parse().unwrap());
+        let mut machine = Machine::new(&instructions);
+        machine.run();
+        assert_eq!(machine[Reg::A], 60);
+        assert_eq!(machine[Reg::B], 6);
+        assert_eq!(machine.mul_count, 1);
+    }
+
+    #[test]
+    fn test_optimize_with_add() {
+        let program = parse(
+            "set b 7\n\
+             set d 2\n\
+             set e 2\n\
+             set g d\n\
+             mul g e\n\
+             sub g b\n\
+             jnz g 2\n\
+             set f 0\n\
+             add e 1\n\
+             set g e\n\
+             sub g b\n\
+             jnz g -8\n\
+             add d 1\n\
+             set g d\n\
+             sub g b\n\
+             jnz g -13\n\
+             set h f",
+        )
+        .unwrap();
+        let optimized = optimize(&program);
+        assert_eq!(optimized.len(), program.len() - 14 + 9);
+        let mut machine = Machine::new(&program);
+        machine[Reg::F] = 1;
+        machine.run();
+        let mut fast = Machine::new(&optimized);
+        fast[Reg::F] = 1;
+        fast.run();
+        assert_eq!(fast[Reg::H], machine[Reg::H]);
+        assert_eq!(fast[Reg::F], machine[Reg::F]);
+    }
+
+    #[test]
+    fn test_optimize_exit_jump() {
+        let program = parse(
+            "set b 7\n\
+             set d 2\n\
+             set e 2\n\
+             set g d\n\
+             mul g e\n\
+             sub g b\n\
+             jnz g 2\n\
+             set f 0\n\
+             sub e -1\n\
+             set g e\n\
+             sub g b\n\
+             jnz g -8\n\
+             sub d -1\n\
+             set g d\n\
+             sub g b\n\
+             jnz g -13\n\
+             jnz 1 -100",
+        )
+        .unwrap();
+        let optimized = optimize(&program);
+        assert_eq!(optimized.last(), Some(&"jnz 1 -95".parse().unwrap()));
+    }
+
+    #[test]
+    fn test_to_common() {
+        let instructions = parse(EXAMPLE).unwrap();
+        let converted = instructions.iter().copied().map(to_common).collect::<Vec<_>>();
+        assert_eq!(converted[4].to_string(), "jnz b -2");
+        let mut machine = common::Machine::new(&converted);
+        assert_eq!(machine.run_until_recover(), None);
+        assert_eq!(machine.mul_count(), part_1(&instructions));
+        assert_eq!(machine[common::Reg::new(b'c').unwrap()], 256);
+    }
+
+    #[test]
+    fn test_rename_registers() {
+        let program = parse("set a 3\nset b 2\nmul b b\nsub a 1\njnz a -2\nsub c b").unwrap();
+        let mut mapping = RegisterMapping::new();
+        assert!(mapping.try_insert(Reg::A, Reg::B));
+        assert!(mapping.try_insert(Reg::B, Reg::A));
+        let renamed = rename_registers(&program, &mapping);
+        assert_eq!(renamed[2].to_string(), "mul a a");
+        assert_eq!(renamed[4].to_string(), "jnz b -2");
+        assert_eq!(renamed[5].to_string(), "sub c a");
+
+        let mut original = Machine::new(&program);
+        original.run();
+        let mut swapped = Machine::new(&renamed);
+        swapped.run();
+        assert_eq!(swapped[Reg::A], original[Reg::B]);
+        assert_eq!(swapped[Reg::B], original[Reg::A]);
+        assert_eq!(swapped[Reg::C], original[Reg::C]);
+        assert_eq!(swapped.mul_count, original.mul_count);
+    }
+}
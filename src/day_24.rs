@@ -1,4 +1,4 @@
-use std::{num::ParseIntError, str::FromStr};
+use std::{collections::VecDeque, num::ParseIntError, str::FromStr};
 
 use thiserror::Error;
 
@@ -11,6 +11,7 @@ enum ParseError {
 }
 
 #[derive(Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 struct Component(u32, u32);
 
 impl Component {
@@ -27,6 +28,32 @@ impl Component {
     const fn strength(self) -> u32 {
         self.0 + self.1
     }
+
+    /// Same component, with the smaller port first.
+    const fn normalized(self) -> Self {
+        if self.0 <= self.1 {
+            self
+        } else {
+            Self(self.1, self.0)
+        }
+    }
+}
+
+impl Ord for Component {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        let Self(a1, b1) = self.normalized();
+        let Self(a2, b2) = other.normalized();
+        (a1, b1, self.strength())
+            .cmp(&(a2, b2, other.strength()))
+            // Keep consistent with Eq for reversed components
+            .then((self.0, self.1).cmp(&(other.0, other.1)))
+    }
+}
+
+impl PartialOrd for Component {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
 }
 
 impl FromStr for Component {
@@ -45,9 +72,14 @@ impl std::fmt::Debug for Component {
     }
 }
 
+/// Parses one component per line, with the smaller port first. Duplicates are kept, since they are
+/// separate components.
 #[aoc_generator(day24)]
 fn parse(input: &str) -> Result<Vec<Component>, ParseError> {
-    input.lines().map(str::parse).collect()
+    input
+        .lines()
+        .map(|line| line.parse().map(Component::normalized))
+        .collect()
 }
 
 #[aoc(day24, part1)]
@@ -114,6 +146,133 @@ fn part_2(components: &[Component]) -> u32 {
     build_bridge(&mut components, 0, 0, 0).1
 }
 
+/// The strength of the strongest bridge, and of the longest bridge, from a single search.
+#[allow(unused)]
+fn solve(components: &[Component]) -> (u32, u32) {
+    let mut components = components.to_vec();
+    let (strongest, (_, longest)) = build_both(&mut components, 0, 0, 0);
+    (strongest, longest)
+}
+
+/// Search behind [`solve`]. Extends the bridge `components[..index]`, ending in `connector`,
+/// and returns the best strength, and the best `(length, strength)`.
+fn build_both(
+    components: &mut [Component],
+    index: usize,
+    connector: u32,
+    accum_strength: u32,
+) -> (u32, (usize, u32)) {
+    let mut strongest = accum_strength;
+    let mut longest = (index, accum_strength);
+    for i in index..components.len() {
+        if let Some(next) = components[i].get_other(connector) {
+            components.swap(index, i);
+            let candidate_strength = components[index].strength();
+            let (total_strength, total_longest) = build_both(
+                components,
+                index + 1,
+                next,
+                accum_strength + candidate_strength,
+            );
+            strongest = strongest.max(total_strength);
+            longest = longest.max(total_longest);
+            components.swap(index, i);
+        }
+    }
+    (strongest, longest)
+}
+
+/// Like [`solve`], but searches from each possible first component in parallel.
+#[cfg(feature = "parallel")]
+fn solve_parallel(components: &[Component]) -> (u32, u32) {
+    use rayon::prelude::*;
+
+    let (strongest, (_, longest)) = (0..components.len())
+        .into_par_iter()
+        .filter_map(|i| {
+            let next = components[i].get_other(0)?;
+            let mut components = components.to_vec();
+            components.swap(0, i);
+            let strength = components[0].strength();
+            Some(build_both(&mut components, 1, next, strength))
+        })
+        .reduce(
+            || (0, (0, 0)),
+            |(s1, l1), (s2, l2)| (s1.max(s2), l1.max(l2)),
+        );
+    (strongest, longest)
+}
+
+#[cfg(feature = "parallel")]
+#[aoc(day24, part1, Parallel)]
+fn part_1_parallel(components: &[Component]) -> u32 {
+    solve_parallel(components).0
+}
+
+#[cfg(feature = "parallel")]
+#[aoc(day24, part2, Parallel)]
+fn part_2_parallel(components: &[Component]) -> u32 {
+    solve_parallel(components).1
+}
+
+/// Indices of the components that are connected to port 0 through other components, in order.
+///
+/// Each component can only be used once in a bridge, so not all of them are necessarily part of
+/// the same bridge.
+#[allow(unused)]
+fn reachable_from_zero(components: &[Component]) -> Vec<usize> {
+    let mut reached = vec![false; components.len()];
+    let mut ports = VecDeque::from([0]);
+    while let Some(port) = ports.pop_front() {
+        for (i, component) in components.iter().enumerate() {
+            if !reached[i]
+                && let Some(other) = component.get_other(port)
+            {
+                reached[i] = true;
+                ports.push_back(other);
+            }
+        }
+    }
+    (0..components.len()).filter(|&i| reached[i]).collect()
+}
+
+/// Calls `visit` on every bridge that can not be extended any further.
+#[allow(unused)]
+fn for_each_bridge<F>(components: &[Component], mut visit: F)
+where
+    F: FnMut(&[Component]),
+{
+    fn build_bridge<F>(components: &mut [Component], index: usize, connector: u32, visit: &mut F)
+    where
+        F: FnMut(&[Component]),
+    {
+        let mut extended = false;
+        for i in index..components.len() {
+            if let Some(next) = components[i].get_other(connector) {
+                components.swap(index, i);
+                build_bridge(components, index + 1, next, visit);
+                components.swap(index, i);
+                extended = true;
+            }
+        }
+        if !extended {
+            visit(&components[..index]);
+        }
+    }
+    let mut components = components.to_vec();
+    build_bridge(&mut components, 0, 0, &mut visit);
+}
+
+/// Whether some bridge uses every one of the components.
+#[allow(unused)]
+fn uses_all(components: &[Component]) -> bool {
+    let mut found = false;
+    for_each_bridge(components, |bridge| found |= bridge.len() == components.len());
+    found
+}
+
+solver!(parse; 1 => part_1, 2 => part_2);
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -132,7 +291,7 @@ mod tests {
                 Component(3, 4),
                 Component(3, 5),
                 Component(0, 1),
-                Component(10, 1),
+                Component(1, 10),
                 Component(9, 10)
             ]
         );
@@ -151,4 +310,102 @@ mod tests {
         let result = part_2(&components);
         assert_eq!(result, 19);
     }
+
+    #[test]
+    fn test_sort_components() {
+        let mut components = vec![
+            Component(5, 3),
+            Component(0, 2),
+            Component(10, 1),
+            Component(3, 5),
+            Component(2, 2),
+        ];
+        components.sort();
+        assert_eq!(
+            components,
+            [
+                Component(0, 2),
+                Component(10, 1),
+                Component(2, 2),
+                Component(3, 5),
+                Component(5, 3),
+            ]
+        );
+        let normalized = components
+            .into_iter()
+            .map(Component::normalized)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            normalized,
+            [
+                Component(0, 2),
+                Component(1, 10),
+                Component(2, 2),
+                Component(3, 5),
+                Component(3, 5)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_for_each_bridge() {
+        let components = parse(EXAMPLE).unwrap();
+        let mut bridges = Vec::new();
+        for_each_bridge(&components, |bridge| bridges.push(bridge.to_vec()));
+        assert_eq!(bridges.len(), 5);
+        assert!(bridges.contains(&vec![Component(0, 1), Component(1, 10), Component(9, 10)]));
+    }
+
+    #[test]
+    fn test_solve() {
+        let components = parse(EXAMPLE).unwrap();
+        assert_eq!(solve(&components), (31, 19));
+        assert_eq!(
+            solve(&components),
+            (part_1(&components), part_2(&components))
+        );
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn test_solve_parallel() {
+        let components = parse(EXAMPLE).unwrap();
+        assert_eq!(solve_parallel(&components), (31, 19));
+        assert_eq!(solve_parallel(&components), solve(&components));
+        assert_eq!(part_1_parallel(&components), part_1(&components));
+        assert_eq!(part_2_parallel(&components), part_2(&components));
+        assert_eq!(solve_parallel(&[Component(1, 2)]), (0, 0));
+    }
+
+    #[test]
+    fn test_reachable_from_zero() {
+        let components = parse(EXAMPLE).unwrap();
+        assert_eq!(reachable_from_zero(&components), [0, 1, 2, 3, 4, 5, 6, 7]);
+        let components = parse("0/1\n1/2\n3/4\n4/4\n2/5").unwrap();
+        assert_eq!(reachable_from_zero(&components), [0, 1, 4]);
+        assert_eq!(reachable_from_zero(&components[2..4]), Vec::<usize>::new());
+    }
+
+    #[test]
+    fn test_uses_all() {
+        let components = parse(EXAMPLE).unwrap();
+        assert!(!uses_all(&components));
+        assert!(uses_all(&parse("0/2\n2/2\n5/2\n5/0").unwrap()));
+        assert!(uses_all(&[]));
+    }
+
+    #[test]
+    fn test_parse_normalizes() {
+        assert_eq!(parse("3/0").unwrap(), parse("0/3").unwrap());
+        assert_eq!(parse("3/0").unwrap(), [Component(0, 3)]);
+        assert_eq!(
+            parse("2/2\n5/2\n2/2").unwrap(),
+            [Component(2, 2), Component(2, 5), Component(2, 2)]
+        );
+        let [component] = parse("7/4").unwrap()[..] else {
+            panic!("expected one component");
+        };
+        assert_eq!(component.get_other(7), Some(4));
+        assert_eq!(component.get_other(4), Some(7));
+    }
 }
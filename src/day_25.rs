@@ -1,4 +1,9 @@
-use std::{collections::VecDeque, num::ParseIntError, ops::Index, str::FromStr};
+use std::{
+    collections::VecDeque,
+    num::ParseIntError,
+    ops::{Index, Range},
+    str::FromStr,
+};
 
 use thiserror::Error;
 
@@ -32,6 +37,7 @@ where
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 enum StateId {
     #[default]
     A,
@@ -65,6 +71,8 @@ impl FromStr for StateId {
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(into = "char", try_from = "char"))]
 enum Symbol {
     #[default]
     Zero,
@@ -89,7 +97,29 @@ impl FromStr for Symbol {
     }
 }
 
+impl TryFrom<char> for Symbol {
+    type Error = ParseError;
+
+    fn try_from(value: char) -> Result<Self, Self::Error> {
+        Ok(match value {
+            '0' => Self::Zero,
+            '1' => Self::One,
+            _ => return Err(ParseError::UnknownSymbol),
+        })
+    }
+}
+
+impl From<Symbol> for char {
+    fn from(value: Symbol) -> Self {
+        match value {
+            Symbol::Zero => '0',
+            Symbol::One => '1',
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 enum Direction {
     #[default]
     Left,
@@ -109,6 +139,7 @@ impl FromStr for Direction {
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 struct Action {
     write: Symbol,
     move_direction: Direction,
@@ -116,6 +147,7 @@ struct Action {
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 struct State {
     id: StateId,
     transitions: [Action; Symbol::all().len()],
@@ -149,6 +181,7 @@ impl Index<Symbol> for State {
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 struct Blueprint {
     initial_state: StateId,
     checksum_after: usize,
@@ -163,6 +196,45 @@ impl Index<StateId> for Blueprint {
     }
 }
 
+impl Blueprint {
+    /// Same blueprint, with the states renamed in the order they are first reached from the
+    /// initial state. Equivalent blueprints have the same canonical form. Unreachable states are
+    /// dropped.
+    #[allow(unused)]
+    fn canonicalize(&self) -> Self {
+        let mut renamed = [None; StateId::all().len()];
+        let mut order = Vec::with_capacity(renamed.len());
+        let mut queue = VecDeque::from([self.initial_state]);
+        renamed[self.initial_state as usize] = Some(StateId::all()[0]);
+        while let Some(id) = queue.pop_front() {
+            order.push(id);
+            for action in self[id].transitions {
+                let next = &mut renamed[action.next_state as usize];
+                if next.is_none() {
+                    *next = Some(StateId::all()[order.len() + queue.len()]);
+                    queue.push_back(action.next_state);
+                }
+            }
+        }
+        let mut states = [State::default(); StateId::all().len()];
+        for id in order {
+            let new_id = renamed[id as usize].unwrap();
+            states[new_id as usize] = State {
+                id: new_id,
+                transitions: self[id].transitions.map(|action| Action {
+                    next_state: renamed[action.next_state as usize].unwrap(),
+                    ..action
+                }),
+            };
+        }
+        Self {
+            initial_state: StateId::all()[0],
+            checksum_after: self.checksum_after,
+            states,
+        }
+    }
+}
+
 impl FromStr for Blueprint {
     type Err = ParseError;
 
@@ -199,11 +271,31 @@ fn part_1(blueprint: &Blueprint) -> usize {
     machine.checksum(Symbol::One)
 }
 
+/// Like [`part_1`], but runs for `steps` steps instead of the number given by the blueprint.
+#[allow(unused)]
+fn run_steps(blueprint: &Blueprint, steps: usize) -> usize {
+    let mut machine = Machine::new(blueprint);
+    machine.run_for(steps);
+    machine.checksum(Symbol::One)
+}
+
+/// Number of distinct tape cells the head visits before the checksum is taken.
+#[aoc(day25, part2)]
+fn part_2(blueprint: &Blueprint) -> usize {
+    let mut machine = Machine::new(blueprint);
+    machine.run();
+    machine.visited_cells()
+}
+
 struct Machine<'a> {
     blueprint: &'a Blueprint,
     state: StateId,
     position: usize,
+    origin: i64, // tape index of the starting cell; can be outside the tape after compact()
     tape: VecDeque<Symbol>,
+    ones_on_tape: usize,
+    leftmost: i64, // head extent, relative to the starting cell
+    rightmost: i64,
 }
 
 impl<'a> Machine<'a> {
@@ -212,7 +304,11 @@ impl<'a> Machine<'a> {
             blueprint,
             state: blueprint.initial_state,
             position: 0,
+            origin: 0,
             tape: [Symbol::default()].into(), // so tape[position] has a value
+            ones_on_tape: 0,
+            leftmost: 0,
+            rightmost: 0,
         }
     }
 
@@ -220,9 +316,11 @@ impl<'a> Machine<'a> {
         if self.position == 0 {
             // push_front moves the zero position.
             self.tape.push_front(Symbol::default());
+            self.origin += 1;
         } else {
             self.position -= 1;
         }
+        self.leftmost = self.leftmost.min(self.head_offset());
     }
 
     fn move_right(&mut self) {
@@ -230,6 +328,17 @@ impl<'a> Machine<'a> {
             self.tape.push_back(Symbol::default());
         }
         self.position += 1;
+        self.rightmost = self.rightmost.max(self.head_offset());
+    }
+
+    /// Position of the head, relative to the starting cell.
+    fn head_offset(&self) -> i64 {
+        i64::try_from(self.position).unwrap() - self.origin
+    }
+
+    /// Number of distinct cells the head has been over, including the starting cell.
+    fn visited_cells(&self) -> usize {
+        usize::try_from(self.rightmost - self.leftmost).unwrap() + 1
     }
 
     fn read(&self) -> Symbol {
@@ -237,7 +346,13 @@ impl<'a> Machine<'a> {
     }
 
     fn write(&mut self, symbol: Symbol) {
-        self.tape[self.position] = symbol;
+        let cell = &mut self.tape[self.position];
+        match (*cell, symbol) {
+            (Symbol::Zero, Symbol::One) => self.ones_on_tape += 1,
+            (Symbol::One, Symbol::Zero) => self.ones_on_tape -= 1,
+            _ => {}
+        }
+        *cell = symbol;
     }
 
     fn tick(&mut self) {
@@ -255,16 +370,81 @@ impl<'a> Machine<'a> {
     }
 
     fn run(&mut self) {
-        for _ in 0..self.blueprint.checksum_after {
+        self.run_for(self.blueprint.checksum_after);
+    }
+
+    fn run_for(&mut self, steps: usize) {
+        for _ in 0..steps {
+            self.tick();
+        }
+    }
+
+    /// Like [`Machine::run`], but calls `report(step, ones)` after every `every` steps.
+    #[allow(unused)]
+    fn run_reporting(&mut self, every: usize, mut report: impl FnMut(usize, usize)) {
+        for step in 1..=self.blueprint.checksum_after {
             self.tick();
+            if step.is_multiple_of(every) {
+                report(step, self.checksum(Symbol::One));
+            }
         }
     }
 
+    /// Number of cells holding `symbol`, from the running count of `One`s.
     fn checksum(&self, symbol: Symbol) -> usize {
+        match symbol {
+            Symbol::One => self.ones_on_tape,
+            Symbol::Zero => self.tape.len() - self.ones_on_tape,
+        }
+    }
+
+    /// Like [`Machine::checksum`], but scans the whole tape.
+    #[allow(unused)]
+    fn checksum_scan(&self, symbol: Symbol) -> usize {
         self.tape.iter().filter(|&&s| s == symbol).count()
     }
+
+    #[allow(unused)]
+    fn tape_len(&self) -> usize {
+        self.tape.len()
+    }
+
+    /// Trims runs of `Zero` from both ends of the tape, keeping the cell under the head.
+    #[allow(unused)]
+    fn compact(&mut self) {
+        let leading = self
+            .tape
+            .range(..self.position)
+            .take_while(|&&s| s == Symbol::Zero)
+            .count();
+        let trailing = self
+            .tape
+            .range(self.position + 1..)
+            .rev()
+            .take_while(|&&s| s == Symbol::Zero)
+            .count();
+        self.tape.truncate(self.tape.len() - trailing);
+        self.tape.drain(..leading);
+        self.position -= leading;
+        self.origin -= i64::try_from(leading).unwrap();
+    }
+
+    /// Like [`Machine::checksum`], but only counts cells in `window`, relative to the starting cell.
+    #[allow(unused)]
+    fn checksum_window(&self, symbol: Symbol, window: Range<i64>) -> usize {
+        let origin = self.origin;
+        let tape_len = i64::try_from(self.tape.len()).unwrap();
+        let start = (window.start + origin).clamp(0, tape_len);
+        let end = (window.end + origin).clamp(start, tape_len);
+        self.tape
+            .range(usize::try_from(start).unwrap()..usize::try_from(end).unwrap())
+            .filter(|&&s| s == symbol)
+            .count()
+    }
 }
 
+solver!(parse; 1 => part_1, 2 => part_2);
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -342,4 +522,112 @@ In state B:
         let result = part_1(&blueprint);
         assert_eq!(result, 3);
     }
+
+    #[test]
+    fn test_checksum_window() {
+        let blueprint = parse(EXAMPLE).unwrap();
+        let mut machine = Machine::new(&blueprint);
+        machine.run();
+        // Tape: 1 1 [0] 1, with the starting cell in brackets.
+        assert_eq!(machine.checksum_window(Symbol::One, -1..1), 1);
+        assert_eq!(machine.checksum_window(Symbol::One, -2..0), 2);
+        assert_eq!(machine.checksum_window(Symbol::One, 0..2), 1);
+        assert_eq!(machine.checksum_window(Symbol::One, -100..100), 3);
+        assert_eq!(machine.checksum_window(Symbol::Zero, 0..1), 1);
+    }
+
+    #[test]
+    fn test_compact() {
+        let blueprint = parse(EXAMPLE).unwrap();
+        let mut machine = Machine::new(&blueprint);
+        machine.run();
+        // Pad the tape with blank cells on both sides
+        for _ in 0..3 {
+            machine.move_left();
+        }
+        for _ in 0..8 {
+            machine.move_right();
+        }
+        for _ in 0..5 {
+            machine.move_left();
+        }
+        assert_eq!(machine.tape_len(), 9);
+        let ones = machine.checksum(Symbol::One);
+        machine.compact();
+        assert_eq!(machine.tape_len(), 4);
+        assert_eq!(machine.checksum(Symbol::One), ones);
+        assert_eq!(machine.read(), Symbol::Zero);
+        assert_eq!(machine.checksum_window(Symbol::One, -2..0), 2);
+    }
+
+    #[test]
+    fn test_run_reporting() {
+        let blueprint = parse(EXAMPLE).unwrap();
+        let mut machine = Machine::new(&blueprint);
+        let mut reports = Vec::new();
+        machine.run_reporting(2, |step, ones| reports.push((step, ones)));
+        assert_eq!(reports, [(2, 2), (4, 2), (6, 3)]);
+    }
+
+    #[test]
+    fn test_canonicalize() {
+        let blueprint = parse(EXAMPLE).unwrap();
+        let swap = |id| match id {
+            StateId::A => StateId::B,
+            StateId::B => StateId::A,
+            other => other,
+        };
+        let mut swapped = blueprint.clone();
+        swapped.initial_state = swap(blueprint.initial_state);
+        for state in &blueprint.states[..2] {
+            swapped.states[swap(state.id) as usize] = State {
+                id: swap(state.id),
+                transitions: state.transitions.map(|action| Action {
+                    next_state: swap(action.next_state),
+                    ..action
+                }),
+            };
+        }
+        assert_ne!(swapped, blueprint);
+        assert_eq!(swapped.canonicalize(), blueprint.canonicalize());
+        assert_eq!(blueprint.canonicalize(), blueprint);
+        assert_eq!(part_1(&swapped), part_1(&blueprint));
+    }
+
+    #[test]
+    fn test_tracked_checksum() {
+        let blueprint = parse(EXAMPLE).unwrap();
+        let mut machine = Machine::new(&blueprint);
+        for _ in 0..blueprint.checksum_after {
+            machine.tick();
+            for symbol in [Symbol::Zero, Symbol::One] {
+                assert_eq!(machine.checksum(symbol), machine.checksum_scan(symbol));
+            }
+        }
+        assert_eq!(machine.checksum(Symbol::One), 3);
+        machine.compact();
+        assert_eq!(machine.checksum(Symbol::Zero), machine.checksum_scan(Symbol::Zero));
+    }
+
+    #[test]
+    fn test_part_2() {
+        let blueprint = parse(EXAMPLE).unwrap();
+        assert_eq!(part_2(&blueprint), 4);
+
+        // Compacting the tape does not forget where the head has been
+        let mut machine = Machine::new(&blueprint);
+        machine.run();
+        machine.compact();
+        assert_eq!(machine.visited_cells(), 4);
+        assert_eq!(machine.head_offset(), 0);
+    }
+
+    #[test]
+    fn test_run_steps() {
+        let blueprint = parse(EXAMPLE).unwrap();
+        assert_eq!(run_steps(&blueprint, 6), part_1(&blueprint));
+        assert_eq!(run_steps(&blueprint, 0), 0);
+        assert_eq!(run_steps(&blueprint, 1), 1);
+        assert_eq!(run_steps(&blueprint, 2), 2);
+    }
 }
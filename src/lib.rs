@@ -5,6 +5,43 @@ extern crate aoc_runner;
 #[macro_use]
 extern crate aoc_runner_derive;
 
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum SolveError {
+    #[error("No solution for day {0}")]
+    UnknownDay(u8),
+    #[error("No solution for part {0}")]
+    UnknownPart(u8),
+    #[error(transparent)]
+    InvalidInput(Box<dyn std::error::Error + Send + Sync>),
+}
+
+/// Defines `solve_part(part, input)` for a day, dispatching to its solvers. Use `parse` when the
+/// day has a generator, or `raw` when the solvers take the input directly.
+macro_rules! solver {
+    (parse; $($part:literal => $solver:ident),+) => {
+        pub fn solve_part(part: u8, input: &str) -> Result<String, crate::SolveError> {
+            match part {
+                $($part => {
+                    let parsed = parse(input).map_err(|err| crate::SolveError::InvalidInput(err.into()))?;
+                    Ok($solver(&parsed).to_string())
+                })+
+                _ => Err(crate::SolveError::UnknownPart(part)),
+            }
+        }
+    };
+    (raw; $($part:literal => $solver:ident),+) => {
+        #[allow(clippy::unnecessary_wraps)]
+        pub fn solve_part(part: u8, input: &str) -> Result<String, crate::SolveError> {
+            match part {
+                $($part => Ok($solver(input.as_ref()).to_string()),)+
+                _ => Err(crate::SolveError::UnknownPart(part)),
+            }
+        }
+    };
+}
+
 mod day_01;
 mod day_02;
 mod day_03;
@@ -31,6 +68,58 @@ mod day_23;
 mod day_24;
 mod day_25;
 
+mod common;
 mod utils;
 
-aoc_lib! { year = 2017 }
\ No newline at end of file
+/// Parses `input` and solves one part of a day, without going through the `cargo-aoc` runner.
+///
+/// # Errors
+///
+/// Fails if the day or part has no solution, or if the input could not be parsed.
+pub fn solve_day(day: u8, part: u8, input: &str) -> Result<String, SolveError> {
+    match day {
+        1 => day_01::solve_part(part, input),
+        2 => day_02::solve_part(part, input),
+        3 => day_03::solve_part(part, input),
+        4 => day_04::solve_part(part, input),
+        5 => day_05::solve_part(part, input),
+        6 => day_06::solve_part(part, input),
+        7 => day_07::solve_part(part, input),
+        8 => day_08::solve_part(part, input),
+        9 => day_09::solve_part(part, input),
+        10 => day_10::solve_part(part, input),
+        11 => day_11::solve_part(part, input),
+        12 => day_12::solve_part(part, input),
+        13 => day_13::solve_part(part, input),
+        14 => day_14::solve_part(part, input),
+        15 => day_15::solve_part(part, input),
+        16 => day_16::solve_part(part, input),
+        17 => day_17::solve_part(part, input),
+        18 => day_18::solve_part(part, input),
+        19 => day_19::solve_part(part, input),
+        20 => day_20::solve_part(part, input),
+        21 => day_21::solve_part(part, input),
+        22 => day_22::solve_part(part, input),
+        23 => day_23::solve_part(part, input),
+        24 => day_24::solve_part(part, input),
+        25 => day_25::solve_part(part, input),
+        _ => Err(SolveError::UnknownDay(day)),
+    }
+}
+
+aoc_lib! { year = 2017 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_solve_day() {
+        assert_eq!(solve_day(13, 1, "0: 3\n1: 2\n4: 4\n6: 4").unwrap(), "24");
+        assert_eq!(solve_day(1, 2, "123123").unwrap(), "12");
+        assert!(matches!(solve_day(13, 1, "0 3"), Err(SolveError::InvalidInput(_))));
+        assert!(matches!(solve_day(6, 1, "1 2 3"), Err(SolveError::InvalidInput(_))));
+        assert!(matches!(solve_day(25, 3, ""), Err(SolveError::UnknownPart(3))));
+        assert!(matches!(solve_day(26, 1, ""), Err(SolveError::UnknownDay(26))));
+    }
+}
\ No newline at end of file
@@ -24,26 +24,36 @@ impl<const N: usize> KnotHasher<N> {
         }
         let mut lengths_vec = Vec::with_capacity(lengths.len() + 5);
         lengths_vec.extend_from_slice(lengths);
-        Self {
+        let hasher = Self {
             lengths: lengths_vec,
             state,
             scratch: [0; N],
             pos: 0,
             skip: 0,
-        }
+        };
+        hasher.check_lengths();
+        hasher
     }
 
     pub fn new(lengths: &[u8]) -> Self {
         let mut hasher = Self::with_raw_lengths(lengths);
         hasher.lengths.extend_from_slice(&[17, 31, 73, 47, 23]);
+        hasher.check_lengths();
         hasher
     }
 
-    pub fn reset(&mut self, lengths: &[u8]) {
+    /// A length longer than the list can not be reversed.
+    fn check_lengths(&self) {
+        if let Some(&len) = self.lengths.iter().find(|&&len| len as usize > N) {
+            panic!("Length {len} is longer than the list of {N} elements");
+        }
+    }
+
+    pub fn reset_raw(&mut self, lengths: &[u8]) {
         self.lengths.clear();
         self.lengths.reserve(lengths.len() + 5);
         self.lengths.extend_from_slice(lengths);
-        self.lengths.extend_from_slice(&[17, 31, 73, 47, 23]);
+        self.check_lengths();
         for (i, x) in self.state.iter_mut().enumerate() {
             *x = u8::try_from(i).unwrap();
         }
@@ -51,6 +61,12 @@ impl<const N: usize> KnotHasher<N> {
         self.skip = 0;
     }
 
+    pub fn reset(&mut self, lengths: &[u8]) {
+        self.reset_raw(lengths);
+        self.lengths.extend_from_slice(&[17, 31, 73, 47, 23]);
+        self.check_lengths();
+    }
+
     pub fn scramble_once(&mut self) {
         for &len in &self.lengths {
             let len = len as usize;
@@ -94,6 +110,31 @@ impl<const N: usize> KnotHasher<N> {
         }
     }
 
+    /// Endless stream of dense hash bytes. After each block of `N / 16` bytes, the state is
+    /// scrambled again, continuing from where the previous block left off.
+    ///
+    /// The stream is deterministic, and is *not* cryptographically secure.
+    pub fn into_stream(mut self) -> impl Iterator<Item = u8> {
+        let mut block = Vec::with_capacity(N / 16);
+        let mut index = 0;
+        std::iter::from_fn(move || {
+            if index == block.len() {
+                if !block.is_empty() {
+                    self.scramble_full();
+                }
+                block.clear();
+                block.extend(
+                    self.state
+                        .chunks(16)
+                        .map(|chunk| chunk.iter().fold(0, |sum, &x| sum ^ x)),
+                );
+                index = 0;
+            }
+            index += 1;
+            Some(block[index - 1])
+        })
+    }
+
     pub fn large_hash(&self) -> String {
         const HEX: &[u8; 16] = b"0123456789abcdef";
         let mut hash = [0; 16];
@@ -178,6 +219,7 @@ impl<T> Grid<T> {
         Self { data, rows, cols }
     }
 
+    /// First position, in row-major order, where `predicate` holds.
     pub fn find_pos<P>(&self, predicate: P) -> Option<(usize, usize)>
     where
         P: FnMut(&T) -> bool,
@@ -188,6 +230,62 @@ impl<T> Grid<T> {
             .map(|index| (index / self.cols, index % self.cols))
     }
 
+    pub fn map<U, F>(&self, f: F) -> Grid<U>
+    where
+        F: Fn(&T) -> U,
+    {
+        Grid {
+            data: self.data.iter().map(f).collect(),
+            rows: self.rows,
+            cols: self.cols,
+        }
+    }
+
+    /// One line per row, with `f` giving the character for each cell.
+    pub fn render_with<F>(&self, f: F) -> String
+    where
+        F: Fn(&T) -> char,
+    {
+        let mut result = String::with_capacity(self.rows * (self.cols + 1));
+        for (r, row) in self.data.chunks(self.cols.max(1)).enumerate() {
+            if r > 0 {
+                result.push('\n');
+            }
+            result.extend(row.iter().map(&f));
+        }
+        result
+    }
+
+    /// The cell at `(row, col)`, or `None` if it is outside the grid.
+    pub fn get(&self, (row, col): (usize, usize)) -> Option<&T> {
+        if row < self.rows && col < self.cols {
+            self.data.get(row * self.cols + col)
+        } else {
+            None
+        }
+    }
+
+    /// Mutable version of [`Grid::get`].
+    pub fn get_mut(&mut self, (row, col): (usize, usize)) -> Option<&mut T> {
+        if row < self.rows && col < self.cols {
+            self.data.get_mut(row * self.cols + col)
+        } else {
+            None
+        }
+    }
+
+    /// The cells of row `r`, left to right.
+    pub fn row(&self, r: usize) -> &[T] {
+        assert!(r < self.rows, "row {r} out of bounds");
+        &self.data[r * self.cols..(r + 1) * self.cols]
+    }
+
+    /// The cells of column `c`, top to bottom.
+    pub fn col(&self, c: usize) -> impl Iterator<Item = &T> {
+        assert!(c < self.cols, "column {c} out of bounds");
+        self.data.iter().skip(c).step_by(self.cols)
+    }
+
     #[must_use]
     pub const fn rows(&self) -> usize {
         self.rows
@@ -247,3 +345,43 @@ impl<T> IndexMut<(usize, usize)> for Grid<T> {
     }
 }
 
+/// A simulation with a deterministic step, so it will eventually loop if it has finitely many
+/// states.
+pub trait CycleState: Eq + Clone {
+    fn step(&mut self);
+}
+
+/// Brent's algorithm. Returns the length of the loop, and number of steps before entering it.
+pub fn brent<S: CycleState>(start: S) -> (usize, usize) {
+    let mut slow = start.clone();
+    let mut fast = start.clone();
+    fast.step();
+
+    let mut power = 1;
+    let mut length = 1;
+
+    while slow != fast {
+        if length == power {
+            slow = fast.clone();
+            power *= 2;
+            length = 0;
+        }
+        fast.step();
+        length += 1;
+    }
+
+    slow = start.clone();
+    fast = start;
+    for _ in 0..length {
+        fast.step();
+    }
+
+    let mut offset = 0;
+    while slow != fast {
+        fast.step();
+        slow.step();
+        offset += 1;
+    }
+
+    (length, offset)
+}